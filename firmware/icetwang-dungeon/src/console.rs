@@ -0,0 +1,185 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Line based debug console on the UART and the USB serial port.
+
+use core::fmt::{self, Write};
+use core::str;
+
+use crate::crash;
+use crate::ledstr_hal::LEDSTR_MAX_LENGTH;
+use crate::serial;
+use crate::uart::Error;
+use crate::{print, println};
+
+const LINE_LENGTH: usize = 32;
+
+const HELP: &str = "\
+help        this text\r\n\
+level N     jump to level N\r\n\
+lives N     set the player lives 1-255\r\n\
+god         toggle god mode\r\n\
+dump        print the world entities\r\n\
+bright N    set the global brightness 0-31\r\n\
+len N       set the LED string length 1-512\r\n\
+prof        print min/avg/max us per frame section and restart\r\n\
+snap        print a game snapshot in hex for the simulator\r\n\
+frames      show the simulator frames until a button is pressed\r\n\
+panic       trigger a panic\r\n\
+crash       print the panic record of the last run\r\n\
+crash clear forget the panic record";
+
+pub enum Command {
+    Level(u32),
+    Lives(u8),
+    God,
+    Dump,
+    Brightness(u16),
+    Length(usize),
+    Profile,
+    Snapshot,
+    Frames,
+    Panic,
+}
+
+pub struct Console {
+    line: [u8; LINE_LENGTH],
+    len: usize,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            line: [0; LINE_LENGTH],
+            len: 0,
+        }
+    }
+
+    /// True while a command is being typed, the status line stays quiet.
+    pub fn editing(&self) -> bool {
+        self.len > 0
+    }
+
+    /// Reads the received characters, returns a command once a line is complete.
+    pub fn poll(&mut self) -> Option<Command> {
+        loop {
+            let c = match serial::read() {
+                Ok(c) => c,
+                Err(nb::Error::Other(Error::Overrun)) => {
+                    // Part of the line is gone, start over
+                    println!("\r\n! input overrun");
+                    self.len = 0;
+                    continue;
+                }
+                Err(nb::Error::WouldBlock) => break,
+            };
+            match c {
+                b'\r' | b'\n' => {
+                    if self.len == 0 {
+                        continue;
+                    }
+                    println!();
+                    let command = self.parse();
+                    self.len = 0;
+                    if command.is_some() {
+                        return command;
+                    }
+                }
+                0x08 | 0x7F if self.len > 0 => {
+                    self.len -= 1;
+                    print!("\x08 \x08");
+                }
+                0x20..=0x7E => {
+                    if self.len == 0 {
+                        print!("\r> \x1b[0K");
+                    }
+                    if self.len < LINE_LENGTH {
+                        self.line[self.len] = c;
+                        self.len += 1;
+                        print!("{}", c as char);
+                    }
+                }
+                _ => ()
+            }
+        }
+        None
+    }
+
+    fn parse(&self) -> Option<Command> {
+        let line = str::from_utf8(&self.line[..self.len]).unwrap_or("");
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let word = words.next();
+        let arg = word.and_then(|w| w.parse::<u32>().ok());
+
+        match (name, arg) {
+            ("level", Some(n)) => Some(Command::Level(n)),
+            ("lives", Some(n)) if n > 0 && n <= u8::MAX as u32 => Some(Command::Lives(n as u8)),
+            ("god", None) => Some(Command::God),
+            ("dump", None) => Some(Command::Dump),
+            ("bright", Some(n)) if n <= 0x1F => Some(Command::Brightness(n as u16)),
+            ("len", Some(n)) if n > 0 && n as usize <= LEDSTR_MAX_LENGTH => Some(Command::Length(n as usize)),
+            ("prof", None) => Some(Command::Profile),
+            ("snap", None) => Some(Command::Snapshot),
+            ("frames", None) => Some(Command::Frames),
+            ("panic", None) => Some(Command::Panic),
+            ("crash", None) if word == Some("clear") => {
+                crash::clear();
+                None
+            }
+            ("crash", None) => {
+                match crash::load() {
+                    Some(record) => {
+                        let _ = writeln!(ConsoleWriter, "{}", record.message());
+                    }
+                    None => println!("no crash recorded"),
+                }
+                None
+            }
+            ("help", None) => {
+                println!("{}", HELP);
+                None
+            }
+            _ => {
+                println!("? {}, try help", line);
+                None
+            }
+        }
+    }
+}
+
+/// Writer turning the `\n` line endings into `\r\n` for the terminal.
+pub struct ConsoleWriter;
+
+impl Write for ConsoleWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                print!("\r\n");
+            }
+            print!("{}", line);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Panic record kept in RAM over resets.
+//!
+//! The record lives in the `crash` region at the end of SPRAM that the
+//! startup code neither clears nor initializes, so it survives a reset (but
+//! not a power cycle). A magic and a checksum tell a valid record apart from
+//! whatever the RAM held at power up.
+
+use core::fmt::{self, Write};
+use core::mem::MaybeUninit;
+use core::panic::PanicInfo;
+use core::ptr;
+use core::str;
+
+const CRASH_MAGIC: u32 = 0x7C4A_5348;
+const CRASH_MESSAGE_SIZE: usize = 244; // Fills the 256 byte region
+
+extern "C" {
+    // Defined in memory.x
+    static mut _crash_record: MaybeUninit<CrashRecord>;
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct CrashRecord {
+    magic: u32,
+    len: u32,
+    message: [u8; CRASH_MESSAGE_SIZE],
+    checksum: u32,
+}
+
+impl CrashRecord {
+    fn new() -> Self {
+        Self {
+            magic: CRASH_MAGIC,
+            len: 0,
+            message: [0; CRASH_MESSAGE_SIZE],
+            checksum: 0,
+        }
+    }
+
+    /// The panic message and location.
+    pub fn message(&self) -> &str {
+        let len = (self.len as usize).min(CRASH_MESSAGE_SIZE);
+        str::from_utf8(&self.message[..len]).unwrap_or("<garbled>")
+    }
+
+    fn sum(&self) -> u32 {
+        self.message.iter().fold(self.magic ^ self.len, |sum, &b| sum.rotate_left(5) ^ b as u32)
+    }
+
+    fn valid(&self) -> bool {
+        self.magic == CRASH_MAGIC && self.checksum == self.sum()
+    }
+}
+
+/// Truncates the panic message to the record size.
+impl Write for CrashRecord {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &b in s.as_bytes() {
+            let len = self.len as usize;
+            if len == CRASH_MESSAGE_SIZE {
+                break;
+            }
+            self.message[len] = b;
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Saves the panic message and location for the next run.
+pub fn store(info: &PanicInfo) {
+    let mut record = CrashRecord::new();
+    let _ = write!(record, "{}", info);
+    record.checksum = record.sum();
+    unsafe {
+        ptr::write_volatile(ptr::addr_of_mut!(_crash_record), MaybeUninit::new(record));
+    }
+}
+
+/// The record left by the last panic, if there is one.
+pub fn load() -> Option<CrashRecord> {
+    // Any bit pattern is a valid CrashRecord, only its checksum might be off
+    let record = unsafe {
+        ptr::read_volatile(ptr::addr_of!(_crash_record)).assume_init()
+    };
+    if record.valid() {
+        Some(record)
+    } else {
+        None
+    }
+}
+
+/// Forgets the stored record.
+pub fn clear() {
+    unsafe {
+        ptr::write_volatile(ptr::addr_of_mut!(_crash_record).cast::<u32>(), 0);
+    }
+}
@@ -26,6 +26,9 @@ use icetwang_pac::LEDSTR;
 use vcell::VolatileCell;
 use core::slice;
 
+/// LEDs the string core's video memory holds
+pub const LEDSTR_MAX_LENGTH: usize = 512;
+
 pub struct LEDStringHAL {
     registers: LEDSTR,
     vmem: &'static mut [VolatileCell<u32>],
@@ -44,7 +47,7 @@ impl LEDStringHAL {
 
         unsafe {
             let vmem =
-                slice::from_raw_parts_mut(vmem_ptr, 512);
+                slice::from_raw_parts_mut(vmem_ptr, LEDSTR_MAX_LENGTH);
             Self { registers, vmem }
         }
     }
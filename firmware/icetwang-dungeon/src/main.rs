@@ -28,25 +28,36 @@
 // Hardware crates
 //extern crate panic_halt;
 
+use core::mem::size_of;
 use core::panic::PanicInfo;
-use core::sync::atomic::{self, Ordering};
 
 use icetwang_pac;
-use ledstr_hal::LEDStringHAL;
+use ledstr_hal::{LEDStringHAL, LEDSTR_MAX_LENGTH};
 use riscv_rt::entry;
 
 mod timer;
 mod rgbled;
 mod print;
+mod uart;
+mod usb;
+mod serial;
 mod ledstr_hal;
 mod joy;
+mod console;
+mod crash;
 
 use timer::Timer;
 use rgbled::RGBLed;
 use joy::Joy;
+use console::{Command, Console, ConsoleWriter};
+use uart::Error;
 
 // Game crates
-mod twang;
+use twang_core::{Config, Twang};
+use twang_core::frame::Decoder;
+use twang_core::input::Input;
+use twang_core::profile::Section;
+use print::{RingLog, UartLogger};
 
 /* 4m long 60 LED/m strip */
 //const LED_STRING_LENGTH: usize = 4 * 60;
@@ -56,6 +67,64 @@ mod twang;
 const LED_STRING_LENGTH: usize = 144;
 const LED_GLOBAL_BRIGHTNESS: u16 = 2; /* for indoor use */
 
+const _: () = assert!(LED_STRING_LENGTH <= LEDSTR_MAX_LENGTH, "the LED string core drives at most 512 LEDs");
+
+/* Stack reserved in SPRAM by stack.x, the game and the frame decoder live on
+   it next to each other, leave 4K for everything else */
+const STACK_SIZE: usize = 12 * 1024;
+const _: () = assert!(size_of::<Twang>() + size_of::<Decoder<LEDSTR_MAX_LENGTH>>() + 4 * 1024 <= STACK_SIZE,
+    "the game and the frame decoder don't fit the stack, raise _stack_size in stack.x");
+
+/* Generate endless levels instead of playing the dungeon, for events */
+const ENDLESS_MODE: bool = false;
+
+/* Snapshot bytes per hex line on the console */
+const SNAPSHOT_LINE: usize = 32;
+
+/// Shows the LED frames the simulator sends until it ends the stream or a
+/// joystick button is pressed. The game stands still meanwhile.
+fn receive_frames(ledstring_hal: &mut LEDStringHAL, joy: &mut Joy) {
+    let mut decoder = Decoder::<LEDSTR_MAX_LENGTH>::new();
+    println!("frames begin");
+    loop {
+        let joystate = joy.get();
+        if joystate.left || joystate.right || joystate.up || joystate.down {
+            break;
+        }
+        // Take everything buffered, from the UART or USB
+        let complete = loop {
+            match serial::read() {
+                Ok(byte) => {
+                    if decoder.push(byte) {
+                        break true;
+                    }
+                }
+                // Part of a frame is gone, wait for the next one
+                Err(nb::Error::Other(Error::Overrun)) => decoder.reset(),
+                Err(nb::Error::WouldBlock) => break false,
+            }
+        };
+        if !complete {
+            continue;
+        }
+        let leds = decoder.leds();
+        if leds.is_empty() {
+            break;
+        }
+
+        // Same orientation as the game, keep the console moving while we wait
+        while ledstring_hal.bsy_n() {
+            serial::poll();
+        }
+        ledstring_hal.set_len(leds.len() as u16 - 1);
+        for (i, led) in leds.iter().enumerate() {
+            ledstring_hal.write_rgb((leds.len() - 1 - i) as u16, *led);
+        }
+        ledstring_hal.start();
+    }
+    println!("frames end");
+}
+
 // This is the entry point for the application.
 // It is not allowed to return.
 
@@ -65,16 +134,15 @@ fn real_main() -> ! {
     // -------------------
     let peripherals = icetwang_pac::Peripherals::take().unwrap();
 
-    // Configure uart for the print macro
-    print::print_hardware::set_hardware(peripherals.UART);
-    print::print_hardware::set_divider(22); // Set baud to 1MBaud
+    // Configure uart and USB serial for the print macro
+    uart::init(peripherals.UART, 22); // Set baud to 1MBaud
+    usb::init(peripherals.USB_CORE, peripherals.USB_DATA);
 
-    // Configure the timer
+    // Configure the timer, it runs free and the frames are paced against it
     let mut timer = Timer::new(peripherals.TIMER);
-    let event_time = 16000;
-    timer.load(event_time); // We want the timer ev to trigger every 1/60th of a second
-    timer.reload(event_time);
-    timer.enable_ev();
+    let event_time = 16000; // We want a frame every 1/60th of a second
+    timer.load(u32::MAX);
+    timer.reload(u32::MAX);
 
     // Configure the RGBLed
     let mut rgbled = RGBLed::new(peripherals.RGBLED);
@@ -100,44 +168,105 @@ fn real_main() -> ! {
 
     // Initialize Game
     // ---------------
-    let mut twang = twang::Twang::new();
-    let mut lr_input: i32;
-    let mut fire_input: bool;
+    let mut twang = Twang::new(Config {
+        led_string_length: LED_STRING_LENGTH,
+        endless: ENDLESS_MODE,
+    });
+    twang.profiler().set_clock(timer::now);
     let mut time: u32 = 0;
+    let mut logger = (UartLogger, RingLog);
+    let mut console = Console::new();
+
+    // Tell about the crash that caused the last reset
+    if crash::load().is_some() {
+        println!("\nThe last run panicked, type `crash` for details");
+    }
 
     // Print debug header
     println!("\nDir  CPU  us");
 
     // Start timer
     timer.enable();
+    let mut frame_start = timer::now();
 
     // Main system loop
     loop {
+        // Handle debug console commands
+        match console.poll() {
+            Some(Command::Level(level)) => twang.start_level(level, time),
+            Some(Command::Lives(lives)) => twang.set_lives(lives),
+            Some(Command::God) => {
+                twang.set_god_mode(!twang.god_mode());
+                println!("god mode {}", if twang.god_mode() {"on"} else {"off"});
+            }
+            Some(Command::Dump) => {
+                let _ = twang.dump(&mut ConsoleWriter);
+            }
+            Some(Command::Brightness(brightness)) => ledstring_hal.set_glob(brightness),
+            Some(Command::Length(len)) => {
+                // Blank the old string so LEDs past the new end go dark
+                while ledstring_hal.bsy_n() {}
+                for i in 0..twang.get_led_len() as u16 {
+                    ledstring_hal.write_rgb(i, [0x00, 0x00, 0x00]);
+                }
+                ledstring_hal.start();
+                while ledstring_hal.bsy_n() {}
+                twang.set_led_len(len);
+                ledstring_hal.set_len(twang.get_led_len() as u16 - 1);
+            }
+            Some(Command::Profile) => {
+                println!("{}", twang.profiler());
+                twang.profiler().reset();
+            }
+            Some(Command::Snapshot) => {
+                // Hex lines between markers, the simulator skips anything else
+                println!("snapshot begin");
+                let mut column = 0;
+                twang.save_snapshot(time, &mut |byte| {
+                    print!("{:02x}", byte);
+                    column += 1;
+                    if column == SNAPSHOT_LINE {
+                        println!();
+                        column = 0;
+                    }
+                });
+                if column > 0 {
+                    println!();
+                }
+                println!("snapshot end");
+            }
+            Some(Command::Frames) => {
+                receive_frames(&mut ledstring_hal, &mut joy);
+                while ledstring_hal.bsy_n() {}
+                ledstring_hal.set_len(twang.get_led_len() as u16 - 1);
+            }
+            Some(Command::Panic) => panic!("requested from the console"),
+            None => ()
+        }
+
         // Get joystick input
         let joystate = joy.get();
-        print!("{}{}{}{}",
-            if joystate.left {"<"} else {" "},
-            if joystate.right {">"} else {" "},
-            if joystate.up {"^"} else {" "},
-            if joystate.down {"v"} else {" "});
-
-        // Cycle game logic
-        lr_input = 0;
-        if joystate.left {
-            lr_input = -10;
-        }
-        if joystate.right {
-            lr_input =  10;
+        if !console.editing() {
+            print!("{}{}{}{}",
+                if joystate.left {"<"} else {" "},
+                if joystate.right {">"} else {" "},
+                if joystate.up {"^"} else {" "},
+                if joystate.down {"v"} else {" "});
         }
-        fire_input = joystate.up || joystate.down;
 
-        twang.cycle(lr_input, fire_input, time);
+        // Cycle game logic
+        let input = Input::from_buttons(joystate.left, joystate.right, joystate.up, joystate.down);
+        twang.cycle(input.lr, input.fire, time);
+        twang.drain_log(&mut logger);
 
         // Make sure the LED string is ready for us
+        twang.profiler().begin(Section::Upload);
         let mut bsy = false;
         while ledstring_hal.bsy_n() {
-            print!("b");
-            bsy = true;
+            if !console.editing() {
+                print!("b");
+                bsy = true;
+            }
         }
         if bsy {
             println!(""); // Add a newline if we printed some delay indicators
@@ -150,17 +279,22 @@ fn real_main() -> ! {
             ledstring_hal.write_rgb((len - 1 - i) as u16, led);
         }
         ledstring_hal.start();
+        twang.profiler().end(Section::Upload);
 
         // Calculate elapsed and percentage of the frame time
-        let time_elapsed = event_time - timer.value();
+        let time_elapsed = timer::now().wrapping_sub(frame_start);
         let busy_percent = (time_elapsed * 100) / event_time;
-        print!(" {:03}% {:5} {:10}\x1b[0K\r", busy_percent, time_elapsed, time);
+        if !console.editing() {
+            print!(" {:03}% {:5} {:10}\x1b[0K\r", busy_percent, time_elapsed, time);
+        }
 
-        // Wait for the timer to expire
-        while !timer.ev_n() {
-            //println!("tmr: {:#010X} {:#06b}", timer.value(), (timer.csr() & 0xFF) as u8);
+        // Wait for the next frame
+        twang.profiler().record(Section::Wait, event_time.saturating_sub(time_elapsed));
+        while timer::now().wrapping_sub(frame_start) < event_time {
+            serial::poll(); // Keep the UART FIFOs moving and answer the USB host
         }
-        timer.ev_rst(); // Reset event
+        // A frame running over skips the frame starts it missed
+        frame_start = frame_start.wrapping_add(timer::now().wrapping_sub(frame_start) / event_time * event_time);
 
         // Advance time (we track time in msec but the timer runs in usec)
         time = time.wrapping_add(event_time/1000);
@@ -175,8 +309,56 @@ fn main() -> ! {
 #[inline(never)]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
+    crash::store(info);
+
+    // The game owns the peripherals, we won't return to it
+    let peripherals = unsafe { icetwang_pac::Peripherals::steal() };
+
+    // Red stripes on the LED string tell a crash from a frozen game. The
+    // strip length may have been changed on the console, take the one the
+    // string core runs with before the HAL resets it.
+    let len = peripherals.LEDSTR.csr.read().len().bits();
+    let mut ledstring_hal = LEDStringHAL::new(peripherals.LEDSTR);
+    ledstring_hal.set_len(len);
+    ledstring_hal.set_div(0);
+    ledstring_hal.set_glob(LED_GLOBAL_BRIGHTNESS);
+    for i in 0..=len {
+        let led = if (i / 4) % 2 == 0 {[0xFF, 0x00, 0x00]} else {[0x00, 0x00, 0x00]};
+        ledstring_hal.write_rgb(i, led);
+    }
+    while ledstring_hal.bsy_n() {}
+    ledstring_hal.start();
+
+    // The RGB LED blinks the panic code in software, the hardware blink
+    // can only do even on/off times
+    let mut rgbled = RGBLed::new(peripherals.RGBLED);
+    rgbled.color(255, 0, 0);
+    rgbled.breathe(false, 0, 0);
+    rgbled.blink(false, 0, 0);
+    let mut timer = Timer::new(peripherals.TIMER);
+    timer.disable();
+    timer.load(16000);
+    timer.reload(16000);
+    timer.enable_ev();
+    timer.enable();
+
     println!("\r\n==> {}", info);
+    println!("Last game events:");
+    print::dump_log_ring();
+    // Stay on the bus, the message goes out as the host reads it
+    let mut frame = 0;
     loop {
-        atomic::compiler_fence(Ordering::SeqCst);
+        serial::poll();
+        if timer.ev_n() {
+            timer.ev_rst();
+            rgbled.state(panic_blink(frame));
+            frame = frame.wrapping_add(1);
+        }
     }
+}
+
+/// RGB LED panic code in 16ms frames, three short flashes and a pause
+fn panic_blink(frame: u32) -> bool {
+    let frame = frame % 96;
+    frame < 36 && frame % 12 < 6
 }
\ No newline at end of file
@@ -22,74 +22,23 @@
  * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
-use icetwang_pac::UART;
+use core::cell::RefCell;
 
-pub struct Uart {
-    pub registers: Option<UART>,
-}
-
-impl Uart {
-    pub fn putc(&self, c: u8) {
-        match self.registers.as_ref() {
-            Some(reg) =>
-                // Wait until TXFULL is `0`
-                //while reg.csr().read().tffull().bit_is_set() {
-                //    ()
-                //}
-                reg.data.write(|w| unsafe {w.databyte().bits(c)}),
-            None => ()
-        }
-    }
-
-    pub fn set_divider(&self, divider: u16) {
-        match self.registers.as_ref() {
-            Some(reg) =>
-                reg.csr.write(|w| unsafe {w.div().bits(divider)}),
-            None => ()
-        }
-    }
-}
+use bare_metal::Mutex;
+use riscv::interrupt;
+use twang_core::log::{Logger, Record, RingLogger};
 
-use core::fmt::{Error, Write};
-impl Write for Uart {
-    fn write_str(&mut self, s: &str) -> Result<(), Error> {
-        for c in s.bytes() {
-            self.putc(c);
-        }
-        Ok(())
-    }
-}
-
-#[macro_use]
-#[cfg(not(test))]
-pub mod print_hardware {
-    use crate::print::*;
-    pub static mut SUPERVISOR_UART: Uart = Uart {
-        registers: None,
-    };
+const LOG_RING_SIZE: usize = 32;
 
-    pub fn set_hardware(uart: UART) {
-        unsafe {
-            SUPERVISOR_UART.registers = Some(uart);
-        }
-    }
-
-    pub fn set_divider(divider: u16) {
-        unsafe {
-            SUPERVISOR_UART.set_divider(divider);
-        }
-    }
+/// Last game log records, for the post-mortem dump in the panic handler
+static LOG_RING: Mutex<RefCell<RingLogger<LOG_RING_SIZE>>> = Mutex::new(RefCell::new(RingLogger::new()));
 
-    #[macro_export]
-    macro_rules! print
-    {
-        ($($args:tt)+) => ({
-                use core::fmt::Write;
-                unsafe {
-                    let _ = write!(crate::print::print_hardware::SUPERVISOR_UART, $($args)+);
-                }
-        });
-    }
+#[macro_export]
+macro_rules! print
+{
+    ($($args:tt)+) => ({
+            $crate::serial::print(format_args!($($args)+));
+    });
 }
 
 #[macro_export]
@@ -106,3 +55,32 @@ macro_rules! println
     });
 }
 
+/// Game log backend writing to the console
+pub struct UartLogger;
+
+impl Logger for UartLogger {
+    fn log(&mut self, record: &Record) {
+        // Overwrite the status line
+        println!("{}\x1b[0K", record);
+    }
+}
+
+/// Game log backend keeping the last records in `LOG_RING`
+pub struct RingLog;
+
+impl Logger for RingLog {
+    fn log(&mut self, record: &Record) {
+        interrupt::free(|cs| LOG_RING.borrow(cs).borrow_mut().log(record));
+    }
+}
+
+/// Prints the records kept by `RingLog`, oldest first.
+pub fn dump_log_ring() {
+    interrupt::free(|cs| {
+        if let Ok(ring) = LOG_RING.borrow(cs).try_borrow() {
+            for record in ring.iter() {
+                println!("{}", record);
+            }
+        }
+    });
+}
@@ -22,47 +22,36 @@
  * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
-/* This is based on the FastLED random8 implementation */
+//! The console goes to both the UART and the USB serial port, input comes
+//! from whichever one the host uses.
 
-#[allow(dead_code)]
-static mut RAND16SEED: u16 = 1337;
+use core::fmt::{self, Write};
 
-#[allow(dead_code)]
-pub fn random16() -> u16 {
-    unsafe {
-        RAND16SEED = (RAND16SEED.wrapping_mul(2053)).wrapping_add(13849);
-        RAND16SEED
-    }
-}
+use embedded_hal::serial::Read;
 
-#[allow(dead_code)]
-pub fn random8() -> u8 {
-    unsafe {
-        RAND16SEED = (RAND16SEED.wrapping_mul(2053)).wrapping_add(13849);
-        ((RAND16SEED & 0xFF) as u8).wrapping_add((RAND16SEED >> 8) as u8)
-    }
-}
+use crate::uart::{self, Error};
+use crate::usb;
 
-#[allow(dead_code)]
-pub fn random8lim(lim: u8) -> u8 {
-    let r = random8();
-    (((r as u32 * lim as u32) >> 8) & 0xFF) as u8
+/// Backend of the `print` macro.
+pub fn print(args: fmt::Arguments) {
+    let _ = uart::with(|uart| uart.write_fmt(args));
+    let _ = usb::with(|usb| usb.write_fmt(args));
 }
 
-#[allow(dead_code)]
-pub fn random8minlim(min: u8, lim: u8) -> u8 {
-    let delta = lim.wrapping_sub(min);
-    random8lim(delta).wrapping_add(min)
+/// Next received byte, from the UART first.
+pub fn read() -> nb::Result<u8, Error> {
+    match uart::with(|uart| uart.read()) {
+        Some(Err(nb::Error::WouldBlock)) | None => (),
+        Some(result) => return result,
+    }
+    match usb::with(|usb| usb.read()) {
+        Some(Ok(byte)) => Ok(byte),
+        _ => Err(nb::Error::WouldBlock),
+    }
 }
 
-#[allow(dead_code)]
-pub fn random16lim(lim: u16) -> u16 {
-    let r = random16();
-    (((r as u32 * lim as u32) >> 16) & 0xFFFF) as u16
+/// Keeps the UART FIFOs moving and answers the USB host.
+pub fn poll() {
+    uart::with(|uart| uart.poll());
+    usb::with(|usb| usb.poll());
 }
-
-#[allow(dead_code)]
-pub fn random16minlim(min: u16, lim: u16) -> u16 {
-    let delta = lim.wrapping_sub(min);
-    random16lim(delta).wrapping_add(min)
-}
\ No newline at end of file
@@ -24,6 +24,15 @@
 
 use icetwang_pac::TIMER;
 
+/// Microseconds since the timer started, the profiler and frame clock. The
+/// main loop lets the timer count down from `u32::MAX` and reload it, so the
+/// clock wraps at 2^32 and `wrapping_sub` measures across the wrap. It only
+/// reads the registers, so it can be used next to the `Timer` owning them.
+pub fn now() -> u32 {
+    let registers = unsafe { &*TIMER::ptr() };
+    !registers.counter.read().bits()
+}
+
 pub struct Timer {
     registers: TIMER,
 }
@@ -0,0 +1,216 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Buffered UART driver.
+//!
+//! The UART has no interrupt, so bytes move between the buffers and the
+//! hardware FIFOs whenever the driver is used and on every `poll`. The one
+//! driver instance is shared through a critical section protected mutex.
+
+use core::cell::RefCell;
+use core::fmt;
+
+use bare_metal::Mutex;
+use embedded_hal::{blocking, serial};
+use icetwang_pac::UART;
+use riscv::interrupt;
+
+const TX_BUFFER_SIZE: usize = 512;
+const RX_BUFFER_SIZE: usize = 64;
+
+static UART_DRIVER: Mutex<RefCell<Option<Uart>>> = Mutex::new(RefCell::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Error {
+    Overrun, // Received bytes were lost, in the hardware FIFO or our buffer
+}
+
+pub struct Ring<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Ring<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Bytes that still fit.
+    pub fn room(&self) -> usize {
+        N - self.len
+    }
+
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.buf[(self.head + self.len) % N] = byte;
+        self.len += 1;
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<u8> {
+        if self.is_empty() {
+            return None;
+        }
+        let byte = self.buf[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(byte)
+    }
+}
+
+pub struct Uart {
+    registers: UART,
+    tx: Ring<TX_BUFFER_SIZE>,
+    rx: Ring<RX_BUFFER_SIZE>,
+    overrun: bool,
+}
+
+#[allow(dead_code)]
+impl Uart {
+    pub fn new(registers: UART) -> Self {
+        Self {
+            registers,
+            tx: Ring::new(),
+            rx: Ring::new(),
+            overrun: false,
+        }
+    }
+
+    pub fn set_divider(&mut self, divider: u16) {
+        self.registers.csr.write(|w| unsafe { w.div().bits(divider) });
+    }
+
+    /// Moves bytes between the buffers and the hardware FIFOs.
+    pub fn poll(&mut self) {
+        while !self.tx.is_empty() && self.registers.csr.read().tffull().bit_is_clear() {
+            if let Some(byte) = self.tx.pop() {
+                self.registers.data.write(|w| unsafe { w.databyte().bits(byte) });
+            }
+        }
+
+        loop {
+            // Reading DATA pops the RX FIFO, RFEMPTY tells if we got a byte
+            let data = self.registers.data.read();
+            if data.rfempty().bit_is_set() {
+                break;
+            }
+            if !self.rx.push(data.databyte().bits()) {
+                self.overrun = true;
+            }
+        }
+
+        if self.registers.csr.read().rfoverflow().bit_is_set() {
+            self.overrun = true;
+            // The flag is cleared by writing it, keep the divider as is
+            self.registers.csr.modify(|_, w| w.rfoverflow().set_bit());
+        }
+    }
+
+    /// True once everything written left the hardware FIFO.
+    pub fn tx_idle(&self) -> bool {
+        self.tx.is_empty() && self.registers.csr.read().tfempty().bit_is_set()
+    }
+}
+
+impl serial::Write<u8> for Uart {
+    type Error = Error;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Error> {
+        self.poll();
+        if !self.tx.push(word) {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.poll();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Error> {
+        self.poll();
+        if self.tx_idle() {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl serial::Read<u8> for Uart {
+    type Error = Error;
+
+    /// Reports a lost byte once, then carries on with the buffered ones.
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        self.poll();
+        if self.overrun {
+            self.overrun = false;
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        self.rx.pop().ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl blocking::serial::write::Default<u8> for Uart {}
+
+impl fmt::Write for Uart {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            // Waits for room in the buffer instead of dropping bytes
+            nb::block!(serial::Write::write(self, byte)).map_err(|_| fmt::Error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hands the UART to the shared driver.
+pub fn init(registers: UART, divider: u16) {
+    let mut uart = Uart::new(registers);
+    uart.set_divider(divider);
+    interrupt::free(|cs| {
+        UART_DRIVER.borrow(cs).replace(Some(uart));
+    });
+}
+
+/// Runs `f` with the shared driver, `None` before `init` or when the driver
+/// is already in use further up the stack (e.g. when panicking while printing).
+pub fn with<R>(f: impl FnOnce(&mut Uart) -> R) -> Option<R> {
+    interrupt::free(|cs| {
+        let mut driver = UART_DRIVER.borrow(cs).try_borrow_mut().ok()?;
+        let result = driver.as_mut().map(f);
+        result
+    })
+}
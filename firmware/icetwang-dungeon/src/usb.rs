@@ -0,0 +1,660 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! USB CDC-ACM serial port on the no2usb core of the SoC.
+//!
+//! The device shows up as a serial port next to a DFU runtime interface, so
+//! the console works over the same cable that flashes the board and
+//! `dfu-util -e` reboots into the bootloader. Like the UART there is no
+//! interrupt, everything moves on `poll`. The one driver instance is shared
+//! through a critical section protected mutex.
+//!
+//! The register layout follows `usb_hw.h` of no2usb. Each endpoint has two
+//! buffer descriptors pointing into the endpoint buffers, which are separate
+//! memories for OUT and IN at the same addresses.
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+use core::fmt;
+use core::ptr;
+use core::sync::atomic::{self, Ordering};
+
+use bare_metal::Mutex;
+use embedded_hal::serial;
+use icetwang_pac::{USB_CORE, USB_DATA};
+use riscv::interrupt;
+
+use crate::uart::Ring;
+
+const TX_BUFFER_SIZE: usize = 512;
+const RX_BUFFER_SIZE: usize = 128;
+const PACKET: usize = 64;       // Max packet size of all endpoints
+const CRC_LEN: usize = 2;       // Received lengths include the CRC16
+const STALL_POLLS: u32 = 20000; // Polls to wait for a reading host before dropping output
+
+// Endpoint buffer layout in bytes
+const EP0_OUT_PTR: u32 = 0;
+const EP0_SETUP_PTR: u32 = 128;
+const EP0_IN_PTR: u32 = 0;
+const DATA_OUT_PTR: [u32; 2] = [256, 384];
+const DATA_IN_PTR: [u32; 2] = [128, 256];
+
+// Endpoint types and buffer descriptor states
+const EP_TYPE_INT: u8 = 2;
+const EP_TYPE_BULK: u8 = 4;
+const EP_TYPE_CTRL: u8 = 6;
+const BD_RDY_DATA: u8 = 2;
+const BD_RDY_STALL: u8 = 3;
+const BD_DONE_OK: u8 = 4;
+const BD_DONE_ERR: u8 = 5;
+
+// Interfaces and endpoints, as in CONF_DESC
+const INTF_CONSOLE: u16 = 0;
+const INTF_DFU: u16 = 2;
+const EP_NOTIFY: usize = 1;
+const EP_DATA: usize = 2;
+
+// Warmboot register of the SoC, not in the SVD. Boot now, image 1 (DFU).
+const WARMBOOT: *mut u32 = 0x8000_0000 as *mut u32;
+const WARMBOOT_DFU: u32 = (1 << 2) | 1;
+
+static USB_DRIVER: Mutex<RefCell<Option<Usb>>> = Mutex::new(RefCell::new(None));
+
+const DEVICE_DESC: [u8; 18] = [
+    18, 0x01,       // Device
+    0x00, 0x02,     // USB 2.0
+    0xEF, 0x02, 0x01, // Interface association
+    PACKET as u8,
+    0x50, 0x1D,     // 1d50:615a, same as the C firmware
+    0x5A, 0x61,
+    0x01, 0x00,     // v0.1
+    1, 2, 0,        // Manufacturer, product, no serial
+    1,              // Configurations
+];
+
+const CONF_DESC_LEN: usize = 93;
+const CONF_DESC: [u8; CONF_DESC_LEN] = [
+    9, 0x02, CONF_DESC_LEN as u8, 0, 3, 1, 0, 0x80, 50, // 3 interfaces, bus powered 100mA
+    // Console, the interface association keeps the CDC pair together
+    8, 0x0B, 0, 2, 0x02, 0x02, 0x00, 3,
+    9, 0x04, 0, 0, 1, 0x02, 0x02, 0x00, 3,      // CDC control, ACM
+    5, 0x24, 0x00, 0x10, 0x01,                  // Header, CDC 1.10
+    5, 0x24, 0x01, 0x00, 1,                     // Call management
+    4, 0x24, 0x02, 0x02,                        // ACM, line coding and state
+    5, 0x24, 0x06, 0, 1,                        // Union
+    7, 0x05, 0x80 | EP_NOTIFY as u8, 0x03, PACKET as u8, 0, 64,
+    9, 0x04, 1, 0, 2, 0x0A, 0x00, 0x00, 0,      // CDC data
+    7, 0x05, EP_DATA as u8, 0x02, PACKET as u8, 0, 0,
+    7, 0x05, 0x80 | EP_DATA as u8, 0x02, PACKET as u8, 0, 0,
+    // DFU runtime, detach reboots into the bootloader
+    9, 0x04, 2, 0, 0, 0xFE, 0x01, 0x01, 4,
+    9, 0x21, 0x0D, 0xE8, 0x03, 0x00, 0x10, 0x01, 0x01,
+];
+
+const STRINGS: [&str; 4] = ["1BitSquared", "icetwang", "Console", "DFU runtime"];
+
+/// Stage of the control transfer on endpoint 0
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Idle,
+    DataIn,
+    DataOut,
+    StatusIn,
+    StatusOut,
+}
+
+struct Setup {
+    request_type: u8,
+    request: u8,
+    value: u16,
+    index: u16,
+    length: u16,
+}
+
+impl Setup {
+    fn parse(p: &[u8; 8]) -> Self {
+        Self {
+            request_type: p[0],
+            request: p[1],
+            value: u16::from_le_bytes([p[2], p[3]]),
+            index: u16::from_le_bytes([p[4], p[5]]),
+            length: u16::from_le_bytes([p[6], p[7]]),
+        }
+    }
+}
+
+pub struct Usb {
+    core: USB_CORE,
+    data: USB_DATA,
+    connected: bool,
+    configured: bool,
+    dtr: bool,              // A program on the host opened the port
+    line_coding: [u8; 7],   // Kept for the host, the bytes go through as they are
+    stage: Stage,
+    request: u8,            // Of the OUT data stage
+    ctrl: [u8; 128],        // Control IN data, fits CONF_DESC
+    ctrl_len: usize,
+    ctrl_pos: usize,
+    ctrl_zlp: bool,         // The IN data ends on a full packet, short of what was asked
+    address: Option<u8>,    // Applied after the status stage
+    detach: bool,           // Reboot into DFU after the status stage
+    out_bd: usize,          // Next data buffer descriptors
+    in_bd: usize,
+    in_zlp: bool,           // Last data packet was full, end the transfer
+    stalled: bool,          // The host stopped reading, drop output
+    tx: Ring<TX_BUFFER_SIZE>,
+    rx: Ring<RX_BUFFER_SIZE>,
+}
+
+#[allow(dead_code)]
+impl Usb {
+    pub fn new(core: USB_CORE, data: USB_DATA) -> Self {
+        let mut usb = Self {
+            core,
+            data,
+            connected: false,
+            configured: false,
+            dtr: false,
+            line_coding: [0x40, 0x42, 0x0F, 0x00, 0, 0, 8], // 1MBaud 8N1 like the UART
+            stage: Stage::Idle,
+            request: 0,
+            ctrl: [0; 128],
+            ctrl_len: 0,
+            ctrl_pos: 0,
+            ctrl_zlp: false,
+            address: None,
+            detach: false,
+            out_bd: 0,
+            in_bd: 0,
+            in_zlp: false,
+            stalled: false,
+            tx: Ring::new(),
+            rx: Ring::new(),
+        };
+        usb.reset();
+        usb
+    }
+
+    /// Enables the pull up, the host enumerates us.
+    pub fn connect(&mut self) {
+        self.connected = true;
+        self.core.csr.modify(|_, w| w.pu_ena().set_bit());
+    }
+
+    pub fn disconnect(&mut self) {
+        self.connected = false;
+        self.core.csr.modify(|_, w| w.pu_ena().clear_bit());
+    }
+
+    /// True while a program on the host has the port open.
+    pub fn listening(&self) -> bool {
+        self.configured && self.dtr
+    }
+
+    /// Answers the host and moves bytes between the buffers and the endpoints.
+    pub fn poll(&mut self) {
+        if self.core.csr.read().bus_rst_pending().bit_is_set() {
+            self.reset();
+            return;
+        }
+        self.poll_control();
+        if self.configured {
+            self.poll_data();
+        }
+    }
+
+    /// True once everything written went to the host, or nobody listens.
+    pub fn tx_idle(&self) -> bool {
+        self.tx.is_empty() || !self.listening() || self.stalled
+    }
+
+    /// Back to the unaddressed, unconfigured state after a bus reset.
+    fn reset(&mut self) {
+        for ep in self.core.ep.iter() {
+            ep.out_status.reset();
+            ep.out_bd0_csr.reset();
+            ep.out_bd1_csr.reset();
+            ep.in_status.reset();
+            ep.in_bd0_csr.reset();
+            ep.in_bd1_csr.reset();
+        }
+
+        // SETUP packets go to descriptor 1, the data stages use descriptor 0
+        let ep0 = &self.core.ep[0];
+        ep0.out_status.write(|w| unsafe { w.ep_type().bits(EP_TYPE_CTRL) }.bd_ctrl().set_bit());
+        ep0.in_status.write(|w| unsafe { w.ep_type().bits(EP_TYPE_CTRL) }.dt().set_bit());
+        ep0.out_bd0_ptr.write(|w| unsafe { w.bits(EP0_OUT_PTR) });
+        ep0.out_bd1_ptr.write(|w| unsafe { w.bits(EP0_SETUP_PTR) });
+        ep0.in_bd0_ptr.write(|w| unsafe { w.bits(EP0_IN_PTR) });
+        ep0.out_bd1_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((8 + CRC_LEN) as u16) });
+
+        let connected = self.connected;
+        self.core.csr.write(|w| unsafe { w.addr().bits(0) }.addr_match().set_bit().cel_ena().set_bit().pu_ena().bit(connected));
+        self.core.ar.write(|w| w.bus_rst_clear().set_bit().sof_clear().set_bit().cel_release().set_bit());
+
+        self.configured = false;
+        self.dtr = false;
+        self.stage = Stage::Idle;
+        self.address = None;
+        self.detach = false;
+    }
+
+    fn read_packet(&self, ptr: u32, out: &mut [u8]) {
+        for (i, chunk) in out.chunks_mut(4).enumerate() {
+            let word = self.data.data[ptr as usize / 4 + i].read().bits().to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+
+    fn write_packet(&self, ptr: u32, data: &[u8]) {
+        for (i, chunk) in data.chunks(4).enumerate() {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.data.data[ptr as usize / 4 + i].write(|w| unsafe { w.bits(u32::from_le_bytes(word)) });
+        }
+    }
+
+    fn poll_control(&mut self) {
+        // A SETUP aborts whatever transfer was going on
+        let setup = self.core.ep[0].out_bd1_csr.read();
+        match setup.state().bits() {
+            BD_DONE_OK => {
+                let mut packet = [0; 8];
+                self.read_packet(EP0_SETUP_PTR, &mut packet);
+                let ep0 = &self.core.ep[0];
+                ep0.out_bd0_csr.reset();
+                ep0.in_bd0_csr.reset();
+                ep0.out_bd1_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((8 + CRC_LEN) as u16) });
+                // Data and status stages start with DATA1
+                ep0.out_status.modify(|_, w| w.dt().set_bit());
+                ep0.in_status.modify(|_, w| w.dt().set_bit());
+                self.setup(&Setup::parse(&packet));
+                self.core.ar.write(|w| w.cel_release().set_bit());
+                return;
+            }
+            BD_DONE_ERR => {
+                self.core.ep[0].out_bd1_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((8 + CRC_LEN) as u16) });
+            }
+            _ => (),
+        }
+
+        let ep0 = &self.core.ep[0];
+        let in_done = ep0.in_bd0_csr.read().state().bits() == BD_DONE_OK;
+        let out = ep0.out_bd0_csr.read();
+        let out_done = out.state().bits() == BD_DONE_OK;
+        match self.stage {
+            Stage::DataIn if in_done => self.send_control(),
+            Stage::StatusOut if out_done => self.stage = Stage::Idle,
+            Stage::DataOut if out_done => {
+                let len = (out.len().bits() as usize).saturating_sub(CRC_LEN);
+                if self.request == CDC_SET_LINE_CODING && len >= self.line_coding.len() {
+                    let mut coding = [0; 7];
+                    self.read_packet(EP0_OUT_PTR, &mut coding);
+                    self.line_coding = coding;
+                }
+                self.status_in();
+            }
+            Stage::StatusIn if in_done => {
+                self.stage = Stage::Idle;
+                if let Some(address) = self.address.take() {
+                    self.core.csr.modify(|_, w| unsafe { w.addr().bits(address) });
+                }
+                if self.detach {
+                    self.reboot_to_dfu();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn setup(&mut self, setup: &Setup) {
+        self.ctrl_len = 0;
+        self.ctrl_pos = 0;
+        if !self.request(setup) {
+            let ep0 = &self.core.ep[0];
+            ep0.out_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_STALL) });
+            ep0.in_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_STALL) });
+            self.stage = Stage::Idle;
+            return;
+        }
+
+        if setup.request_type & 0x80 != 0 {
+            let asked = setup.length as usize;
+            self.ctrl_zlp = self.ctrl_len < asked && self.ctrl_len % PACKET == 0;
+            self.ctrl_len = self.ctrl_len.min(asked);
+            self.stage = Stage::DataIn;
+            self.send_control();
+        } else if setup.length > 0 {
+            self.request = setup.request;
+            self.stage = Stage::DataOut;
+            self.arm_control_out();
+        } else {
+            self.status_in();
+        }
+    }
+
+    /// Handles a request, leaving the IN data in `ctrl`. False stalls it.
+    fn request(&mut self, setup: &Setup) -> bool {
+        let recipient = setup.request_type & 0x1F;
+        match (setup.request_type & 0x60, recipient, setup.request) {
+            (REQ_STANDARD, _, GET_STATUS) => self.reply(&[0, 0]),
+            (REQ_STANDARD, RCPT_DEVICE, CLEAR_FEATURE) | (REQ_STANDARD, RCPT_DEVICE, SET_FEATURE) => true,
+            (REQ_STANDARD, RCPT_DEVICE, SET_ADDRESS) => {
+                self.address = Some(setup.value as u8 & 0x7F);
+                true
+            }
+            (REQ_STANDARD, RCPT_DEVICE, GET_DESCRIPTOR) => self.descriptor(setup.value),
+            (REQ_STANDARD, RCPT_DEVICE, GET_CONFIGURATION) => self.reply(&[self.configured as u8]),
+            (REQ_STANDARD, RCPT_DEVICE, SET_CONFIGURATION) => match setup.value {
+                0 => {
+                    self.configured = false;
+                    true
+                }
+                1 => {
+                    self.configure();
+                    true
+                }
+                _ => false,
+            },
+            (REQ_STANDARD, RCPT_INTERFACE, GET_INTERFACE) => self.reply(&[0]),
+            (REQ_STANDARD, RCPT_INTERFACE, SET_INTERFACE) => setup.value == 0,
+            (REQ_STANDARD, RCPT_ENDPOINT, CLEAR_FEATURE) => {
+                // Endpoint halt, the data toggle starts over
+                let ep = &self.core.ep[(setup.index & 0x0F) as usize];
+                if setup.index & 0x80 != 0 {
+                    ep.in_status.modify(|_, w| w.dt().clear_bit());
+                } else {
+                    ep.out_status.modify(|_, w| w.dt().clear_bit());
+                }
+                true
+            }
+            (REQ_CLASS, RCPT_INTERFACE, request) if setup.index == INTF_CONSOLE => match request {
+                CDC_SET_LINE_CODING | CDC_SEND_BREAK => true,
+                CDC_GET_LINE_CODING => {
+                    let coding = self.line_coding;
+                    self.reply(&coding)
+                }
+                CDC_SET_CONTROL_LINE_STATE => {
+                    self.dtr = setup.value & 1 != 0;
+                    true
+                }
+                _ => false,
+            },
+            (REQ_CLASS, RCPT_INTERFACE, request) if setup.index == INTF_DFU => match request {
+                DFU_DETACH => {
+                    self.detach = true;
+                    true
+                }
+                DFU_GETSTATUS => self.reply(&[0, 0, 0, 0, DFU_APP_IDLE, 0]),
+                DFU_GETSTATE => self.reply(&[DFU_APP_IDLE]),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn reply(&mut self, data: &[u8]) -> bool {
+        self.ctrl[..data.len()].copy_from_slice(data);
+        self.ctrl_len = data.len();
+        true
+    }
+
+    fn descriptor(&mut self, value: u16) -> bool {
+        let index = (value & 0xFF) as usize;
+        match (value >> 8) as u8 {
+            DT_DEVICE => self.reply(&DEVICE_DESC),
+            DT_CONFIG if index == 0 => self.reply(&CONF_DESC),
+            DT_STRING if index == 0 => self.reply(&[4, DT_STRING, 0x09, 0x04]), // US English
+            DT_STRING if index <= STRINGS.len() => {
+                // UTF-16, the strings are ASCII
+                let text = STRINGS[index - 1].as_bytes();
+                self.ctrl_len = 2 + 2 * text.len();
+                self.ctrl[0] = self.ctrl_len as u8;
+                self.ctrl[1] = DT_STRING;
+                for (i, &c) in text.iter().enumerate() {
+                    self.ctrl[2 + 2 * i] = c;
+                    self.ctrl[3 + 2 * i] = 0;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sends the next packet of the control IN data, or moves on to the
+    /// status stage once all went out.
+    fn send_control(&mut self) {
+        let left = self.ctrl_len - self.ctrl_pos;
+        if left == 0 && !self.ctrl_zlp {
+            self.stage = Stage::StatusOut;
+            self.arm_control_out();
+            return;
+        }
+        let len = left.min(PACKET);
+        if len == 0 {
+            self.ctrl_zlp = false;
+        }
+        self.write_packet(EP0_IN_PTR, &self.ctrl[self.ctrl_pos..self.ctrl_pos + len]);
+        self.core.ep[0].in_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits(len as u16) });
+        self.ctrl_pos += len;
+    }
+
+    fn arm_control_out(&mut self) {
+        self.core.ep[0].out_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((PACKET + CRC_LEN) as u16) });
+    }
+
+    /// Acknowledges the request with an empty IN packet.
+    fn status_in(&mut self) {
+        self.stage = Stage::StatusIn;
+        self.core.ep[0].in_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits(0) });
+    }
+
+    /// Sets up the console endpoints for SET_CONFIGURATION.
+    fn configure(&mut self) {
+        let ep = &self.core.ep[EP_DATA];
+        ep.out_status.write(|w| unsafe { w.ep_type().bits(EP_TYPE_BULK) }.bd_dual().set_bit());
+        ep.out_bd0_ptr.write(|w| unsafe { w.bits(DATA_OUT_PTR[0]) });
+        ep.out_bd1_ptr.write(|w| unsafe { w.bits(DATA_OUT_PTR[1]) });
+        ep.out_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((PACKET + CRC_LEN) as u16) });
+        ep.out_bd1_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((PACKET + CRC_LEN) as u16) });
+        ep.in_status.write(|w| unsafe { w.ep_type().bits(EP_TYPE_BULK) }.bd_dual().set_bit());
+        ep.in_bd0_ptr.write(|w| unsafe { w.bits(DATA_IN_PTR[0]) });
+        ep.in_bd1_ptr.write(|w| unsafe { w.bits(DATA_IN_PTR[1]) });
+        ep.in_bd0_csr.reset();
+        ep.in_bd1_csr.reset();
+
+        // No serial state notifications, the endpoint just NAKs
+        self.core.ep[EP_NOTIFY].in_status.write(|w| unsafe { w.ep_type().bits(EP_TYPE_INT) });
+
+        self.out_bd = 0;
+        self.in_bd = 0;
+        self.in_zlp = false;
+        self.configured = true;
+    }
+
+    fn poll_data(&mut self) {
+        // Received packets wait in the endpoint until they fit, the host gets NAKs meanwhile
+        loop {
+            let ep = &self.core.ep[EP_DATA];
+            let csr = if self.out_bd == 0 { ep.out_bd0_csr.read().bits() } else { ep.out_bd1_csr.read().bits() };
+            let state = (csr >> 13) as u8 & 0x07;
+            let len = ((csr & 0x03FF) as usize).saturating_sub(CRC_LEN);
+            if state != BD_DONE_OK && state != BD_DONE_ERR {
+                break;
+            }
+            if state == BD_DONE_OK {
+                if len > self.rx.room() {
+                    break;
+                }
+                let mut packet = [0; PACKET];
+                self.read_packet(DATA_OUT_PTR[self.out_bd], &mut packet[..len]);
+                for &byte in &packet[..len] {
+                    self.rx.push(byte);
+                }
+            }
+            let ep = &self.core.ep[EP_DATA];
+            if self.out_bd == 0 {
+                ep.out_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((PACKET + CRC_LEN) as u16) });
+            } else {
+                ep.out_bd1_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits((PACKET + CRC_LEN) as u16) });
+            }
+            self.out_bd ^= 1;
+        }
+
+        // Fill the free IN descriptors
+        loop {
+            let ep = &self.core.ep[EP_DATA];
+            let state = if self.in_bd == 0 { ep.in_bd0_csr.read().state().bits() } else { ep.in_bd1_csr.read().state().bits() };
+            if state == BD_RDY_DATA || (self.tx.is_empty() && !self.in_zlp) {
+                break;
+            }
+            let mut packet = [0; PACKET];
+            let mut len = 0;
+            while len < PACKET {
+                match self.tx.pop() {
+                    Some(byte) => packet[len] = byte,
+                    None => break,
+                }
+                len += 1;
+            }
+            self.write_packet(DATA_IN_PTR[self.in_bd], &packet[..len]);
+            let ep = &self.core.ep[EP_DATA];
+            if self.in_bd == 0 {
+                ep.in_bd0_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits(len as u16) });
+            } else {
+                ep.in_bd1_csr.write(|w| unsafe { w.state().bits(BD_RDY_DATA).len().bits(len as u16) });
+            }
+            // A full packet doesn't end the transfer on the host
+            self.in_zlp = len == PACKET;
+            self.in_bd ^= 1;
+            self.stalled = false;
+        }
+    }
+
+    fn reboot_to_dfu(&mut self) -> ! {
+        self.disconnect();
+        unsafe { ptr::write_volatile(WARMBOOT, WARMBOOT_DFU) };
+        loop {
+            atomic::compiler_fence(Ordering::SeqCst);
+        }
+    }
+}
+
+// Request types, recipients and the requests we answer
+const REQ_STANDARD: u8 = 0x00;
+const REQ_CLASS: u8 = 0x20;
+const RCPT_DEVICE: u8 = 0;
+const RCPT_INTERFACE: u8 = 1;
+const RCPT_ENDPOINT: u8 = 2;
+const GET_STATUS: u8 = 0;
+const CLEAR_FEATURE: u8 = 1;
+const SET_FEATURE: u8 = 3;
+const SET_ADDRESS: u8 = 5;
+const GET_DESCRIPTOR: u8 = 6;
+const GET_CONFIGURATION: u8 = 8;
+const SET_CONFIGURATION: u8 = 9;
+const GET_INTERFACE: u8 = 10;
+const SET_INTERFACE: u8 = 11;
+const DT_DEVICE: u8 = 1;
+const DT_CONFIG: u8 = 2;
+const DT_STRING: u8 = 3;
+const CDC_SET_LINE_CODING: u8 = 0x20;
+const CDC_GET_LINE_CODING: u8 = 0x21;
+const CDC_SET_CONTROL_LINE_STATE: u8 = 0x22;
+const CDC_SEND_BREAK: u8 = 0x23;
+const DFU_DETACH: u8 = 0;
+const DFU_GETSTATUS: u8 = 3;
+const DFU_GETSTATE: u8 = 5;
+const DFU_APP_IDLE: u8 = 0;
+
+impl serial::Write<u8> for Usb {
+    type Error = Infallible;
+
+    /// Drops the byte while nobody listens, waits a little for a slow host.
+    fn write(&mut self, word: u8) -> nb::Result<(), Infallible> {
+        if !self.listening() {
+            return Ok(());
+        }
+        if self.tx.is_full() && !self.stalled {
+            for _ in 0..STALL_POLLS {
+                self.poll();
+                if !self.tx.is_full() {
+                    break;
+                }
+            }
+            self.stalled = self.tx.is_full();
+        }
+        self.tx.push(word);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        self.poll();
+        if self.tx_idle() {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl serial::Read<u8> for Usb {
+    type Error = Infallible;
+
+    fn read(&mut self) -> nb::Result<u8, Infallible> {
+        if self.rx.is_empty() {
+            self.poll();
+        }
+        self.rx.pop().ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl fmt::Write for Usb {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            let _ = serial::Write::write(self, byte);
+        }
+        self.poll();
+        Ok(())
+    }
+}
+
+/// Hands the USB core to the shared driver and connects to the host.
+pub fn init(core: USB_CORE, data: USB_DATA) {
+    let mut usb = Usb::new(core, data);
+    usb.connect();
+    interrupt::free(|cs| {
+        USB_DRIVER.borrow(cs).replace(Some(usb));
+    });
+}
+
+/// Runs `f` with the shared driver, `None` before `init` or when the driver
+/// is already in use further up the stack.
+pub fn with<R>(f: impl FnOnce(&mut Usb) -> R) -> Option<R> {
+    interrupt::free(|cs| {
+        let mut driver = USB_DRIVER.borrow(cs).try_borrow_mut().ok()?;
+        let result = driver.as_mut().map(f);
+        result
+    })
+}
@@ -0,0 +1,7 @@
+/* Stack of the dungeon firmware, the game and the simulator frame decoder
+   live on it. Keep it in sync with STACK_SIZE in main.rs. The link fails if
+   the code and the statics leave less than that in SPRAM. */
+_stack_size = 12K;
+
+ASSERT(_stack_start - _ebss >= _stack_size, "
+ERROR(icetwang-dungeon): the code and statics leave less than _stack_size of SPRAM for the stack.");
@@ -1,6 +1,7 @@
 MEMORY {
 	csr :   ORIGIN = 0x80000000, LENGTH = 0x10000000
-	spram : ORIGIN = 0x00020000, LENGTH = 0x00010000
+	spram : ORIGIN = 0x00020000, LENGTH = 0x0000ff00
+	crash : ORIGIN = 0x0002ff00, LENGTH = 0x00000100 /* kept over resets */
 	bram:   ORIGIN = 0x00000010, LENGTH = 0x000003f0
 	/*spiflash : ORIGIN = 0x20000000, LENGTH = 0x01000000*/
 	/*rom : ORIGIN = 0x20040000, LENGTH = 0x00fc0000*/
@@ -13,5 +14,8 @@ REGION_ALIAS("REGION_BSS", spram);
 REGION_ALIAS("REGION_HEAP", spram);
 REGION_ALIAS("REGION_STACK", spram);
 
+/* Panic record of the last run, see crash.rs in the dungeon firmware. */
+_crash_record = ORIGIN(crash);
+
 /* CPU reset location. */
 _stext = 0x00020000;
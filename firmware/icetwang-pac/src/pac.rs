@@ -865,7 +865,550 @@ pub fn ack (& mut self) -> ACK_W { ACK_W { w : self } } # [doc = "Bits 12:13 - S
 # [inline (always)]
 pub fn cmd (& mut self) -> CMD_W { CMD_W { w : self } } # [doc = "Bit 31 - I2C peripheral ready"]
 # [inline (always)]
-pub fn ready (& mut self) -> READY_W { READY_W { w : self } } } } } # [no_mangle]
+pub fn ready (& mut self) -> READY_W { READY_W { w : self } } } } } # [doc = "USB core control"]
+pub struct USB_CORE { _marker : PhantomData < * const () > } unsafe impl Send for USB_CORE { } impl USB_CORE { # [doc = r"Returns a pointer to the register block"]
+# [inline (always)]
+pub const fn ptr () -> * const usb_core :: RegisterBlock { 0x8400_0000 as * const _ } } impl Deref for USB_CORE { type Target = usb_core :: RegisterBlock ; # [inline (always)]
+fn deref (& self) -> & Self :: Target { unsafe { & * USB_CORE :: ptr () } } } # [doc = "USB core control"]
+pub mod usb_core { # [doc = r"Register block"]
+# [repr (C)]
+pub struct RegisterBlock { # [doc = "0x00 - Control and Status Register"]
+pub csr : CSR , # [doc = "0x04 - Action Register"]
+pub ar : AR , _reserved2 : [u8 ; 8184usize]
+, # [doc = "0x2000 - Endpoint pair registers"]
+pub ep : [EP ; 16]
+, } # [doc = "Control and Status Register\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [csr](csr) module"]
+pub type CSR = crate :: Reg < u32 , _CSR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _CSR ; # [doc = "`read()` method returns [csr::R](csr::R) reader structure"]
+impl crate :: Readable for CSR { } # [doc = "`write(|w| ..)` method takes [csr::W](csr::W) writer structure"]
+impl crate :: Writable for CSR { } # [doc = "Control and Status Register"]
+pub mod csr { # [doc = "Reader of register CSR"]
+pub type R = crate :: R < u32 , super :: CSR > ; # [doc = "Writer for register CSR"]
+pub type W = crate :: W < u32 , super :: CSR > ; # [doc = "Register CSR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: CSR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `ADDR`"]
+pub type ADDR_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `ADDR`"]
+pub struct ADDR_W < 'a > { w : & 'a mut W , } impl < 'a > ADDR_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x7f) | ((value as u32) & 0x7f) ; self . w } } # [doc = "Reader of field `ADDR_MATCH`"]
+pub type ADDR_MATCH_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `ADDR_MATCH`"]
+pub struct ADDR_MATCH_W < 'a > { w : & 'a mut W , } impl < 'a > ADDR_MATCH_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 7)) | (((value as u32) & 0x01) << 7) ; self . w } } # [doc = "Reader of field `SOF_PENDING`"]
+pub type SOF_PENDING_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `SOF_PENDING`"]
+pub struct SOF_PENDING_W < 'a > { w : & 'a mut W , } impl < 'a > SOF_PENDING_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 8)) | (((value as u32) & 0x01) << 8) ; self . w } } # [doc = "Reader of field `BUS_RST_PENDING`"]
+pub type BUS_RST_PENDING_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BUS_RST_PENDING`"]
+pub struct BUS_RST_PENDING_W < 'a > { w : & 'a mut W , } impl < 'a > BUS_RST_PENDING_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 9)) | (((value as u32) & 0x01) << 9) ; self . w } } # [doc = "Reader of field `BUS_RST`"]
+pub type BUS_RST_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BUS_RST`"]
+pub struct BUS_RST_W < 'a > { w : & 'a mut W , } impl < 'a > BUS_RST_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 10)) | (((value as u32) & 0x01) << 10) ; self . w } } # [doc = "Reader of field `BUS_SUSPEND`"]
+pub type BUS_SUSPEND_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BUS_SUSPEND`"]
+pub struct BUS_SUSPEND_W < 'a > { w : & 'a mut W , } impl < 'a > BUS_SUSPEND_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 11)) | (((value as u32) & 0x01) << 11) ; self . w } } # [doc = "Reader of field `CEL_ENA`"]
+pub type CEL_ENA_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `CEL_ENA`"]
+pub struct CEL_ENA_W < 'a > { w : & 'a mut W , } impl < 'a > CEL_ENA_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 12)) | (((value as u32) & 0x01) << 12) ; self . w } } # [doc = "Reader of field `CEL_ACTIVE`"]
+pub type CEL_ACTIVE_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `CEL_ACTIVE`"]
+pub struct CEL_ACTIVE_W < 'a > { w : & 'a mut W , } impl < 'a > CEL_ACTIVE_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 13)) | (((value as u32) & 0x01) << 13) ; self . w } } # [doc = "Reader of field `EVT_PENDING`"]
+pub type EVT_PENDING_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `EVT_PENDING`"]
+pub struct EVT_PENDING_W < 'a > { w : & 'a mut W , } impl < 'a > EVT_PENDING_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 14)) | (((value as u32) & 0x01) << 14) ; self . w } } # [doc = "Reader of field `PU_ENA`"]
+pub type PU_ENA_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `PU_ENA`"]
+pub struct PU_ENA_W < 'a > { w : & 'a mut W , } impl < 'a > PU_ENA_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 15)) | (((value as u32) & 0x01) << 15) ; self . w } } impl R { # [doc = "Bits 0:6 - Device address"]
+# [inline (always)]
+pub fn addr (& self) -> ADDR_R { ADDR_R :: new ((self . bits & 0x7f) as u8) } # [doc = "Bit 7 - Only answer to the device address"]
+# [inline (always)]
+pub fn addr_match (& self) -> ADDR_MATCH_R { ADDR_MATCH_R :: new (((self . bits >> 7) & 0x01) != 0) } # [doc = "Bit 8 - A start of frame was received"]
+# [inline (always)]
+pub fn sof_pending (& self) -> SOF_PENDING_R { SOF_PENDING_R :: new (((self . bits >> 8) & 0x01) != 0) } # [doc = "Bit 9 - A bus reset was detected"]
+# [inline (always)]
+pub fn bus_rst_pending (& self) -> BUS_RST_PENDING_R { BUS_RST_PENDING_R :: new (((self . bits >> 9) & 0x01) != 0) } # [doc = "Bit 10 - Bus reset state"]
+# [inline (always)]
+pub fn bus_rst (& self) -> BUS_RST_R { BUS_RST_R :: new (((self . bits >> 10) & 0x01) != 0) } # [doc = "Bit 11 - Bus suspend state"]
+# [inline (always)]
+pub fn bus_suspend (& self) -> BUS_SUSPEND_R { BUS_SUSPEND_R :: new (((self . bits >> 11) & 0x01) != 0) } # [doc = "Bit 12 - Enable the control endpoint lockout after every SETUP"]
+# [inline (always)]
+pub fn cel_ena (& self) -> CEL_ENA_R { CEL_ENA_R :: new (((self . bits >> 12) & 0x01) != 0) } # [doc = "Bit 13 - Control endpoint lockout active"]
+# [inline (always)]
+pub fn cel_active (& self) -> CEL_ACTIVE_R { CEL_ACTIVE_R :: new (((self . bits >> 13) & 0x01) != 0) } # [doc = "Bit 14 - Event pending"]
+# [inline (always)]
+pub fn evt_pending (& self) -> EVT_PENDING_R { EVT_PENDING_R :: new (((self . bits >> 14) & 0x01) != 0) } # [doc = "Bit 15 - Enable the D+ pull up, connecting the device"]
+# [inline (always)]
+pub fn pu_ena (& self) -> PU_ENA_R { PU_ENA_R :: new (((self . bits >> 15) & 0x01) != 0) } } impl W { # [doc = "Bits 0:6 - Device address"]
+# [inline (always)]
+pub fn addr (& mut self) -> ADDR_W { ADDR_W { w : self } } # [doc = "Bit 7 - Only answer to the device address"]
+# [inline (always)]
+pub fn addr_match (& mut self) -> ADDR_MATCH_W { ADDR_MATCH_W { w : self } } # [doc = "Bit 8 - A start of frame was received"]
+# [inline (always)]
+pub fn sof_pending (& mut self) -> SOF_PENDING_W { SOF_PENDING_W { w : self } } # [doc = "Bit 9 - A bus reset was detected"]
+# [inline (always)]
+pub fn bus_rst_pending (& mut self) -> BUS_RST_PENDING_W { BUS_RST_PENDING_W { w : self } } # [doc = "Bit 10 - Bus reset state"]
+# [inline (always)]
+pub fn bus_rst (& mut self) -> BUS_RST_W { BUS_RST_W { w : self } } # [doc = "Bit 11 - Bus suspend state"]
+# [inline (always)]
+pub fn bus_suspend (& mut self) -> BUS_SUSPEND_W { BUS_SUSPEND_W { w : self } } # [doc = "Bit 12 - Enable the control endpoint lockout after every SETUP"]
+# [inline (always)]
+pub fn cel_ena (& mut self) -> CEL_ENA_W { CEL_ENA_W { w : self } } # [doc = "Bit 13 - Control endpoint lockout active"]
+# [inline (always)]
+pub fn cel_active (& mut self) -> CEL_ACTIVE_W { CEL_ACTIVE_W { w : self } } # [doc = "Bit 14 - Event pending"]
+# [inline (always)]
+pub fn evt_pending (& mut self) -> EVT_PENDING_W { EVT_PENDING_W { w : self } } # [doc = "Bit 15 - Enable the D+ pull up, connecting the device"]
+# [inline (always)]
+pub fn pu_ena (& mut self) -> PU_ENA_W { PU_ENA_W { w : self } } } } # [doc = "Action Register\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [ar](ar) module"]
+pub type AR = crate :: Reg < u32 , _AR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _AR ; # [doc = "`read()` method returns [ar::R](ar::R) reader structure"]
+impl crate :: Readable for AR { } # [doc = "`write(|w| ..)` method takes [ar::W](ar::W) writer structure"]
+impl crate :: Writable for AR { } # [doc = "Action Register"]
+pub mod ar { # [doc = "Reader of register AR"]
+pub type R = crate :: R < u32 , super :: AR > ; # [doc = "Writer for register AR"]
+pub type W = crate :: W < u32 , super :: AR > ; # [doc = "Register AR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: AR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `SOF_CLEAR`"]
+pub type SOF_CLEAR_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `SOF_CLEAR`"]
+pub struct SOF_CLEAR_W < 'a > { w : & 'a mut W , } impl < 'a > SOF_CLEAR_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 8)) | (((value as u32) & 0x01) << 8) ; self . w } } # [doc = "Reader of field `BUS_RST_CLEAR`"]
+pub type BUS_RST_CLEAR_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BUS_RST_CLEAR`"]
+pub struct BUS_RST_CLEAR_W < 'a > { w : & 'a mut W , } impl < 'a > BUS_RST_CLEAR_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 9)) | (((value as u32) & 0x01) << 9) ; self . w } } # [doc = "Reader of field `CEL_RELEASE`"]
+pub type CEL_RELEASE_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `CEL_RELEASE`"]
+pub struct CEL_RELEASE_W < 'a > { w : & 'a mut W , } impl < 'a > CEL_RELEASE_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 13)) | (((value as u32) & 0x01) << 13) ; self . w } } impl R { # [doc = "Bit 8 - Write 1 to clear SOF_PENDING"]
+# [inline (always)]
+pub fn sof_clear (& self) -> SOF_CLEAR_R { SOF_CLEAR_R :: new (((self . bits >> 8) & 0x01) != 0) } # [doc = "Bit 9 - Write 1 to clear BUS_RST_PENDING"]
+# [inline (always)]
+pub fn bus_rst_clear (& self) -> BUS_RST_CLEAR_R { BUS_RST_CLEAR_R :: new (((self . bits >> 9) & 0x01) != 0) } # [doc = "Bit 13 - Write 1 to release the control endpoint lockout"]
+# [inline (always)]
+pub fn cel_release (& self) -> CEL_RELEASE_R { CEL_RELEASE_R :: new (((self . bits >> 13) & 0x01) != 0) } } impl W { # [doc = "Bit 8 - Write 1 to clear SOF_PENDING"]
+# [inline (always)]
+pub fn sof_clear (& mut self) -> SOF_CLEAR_W { SOF_CLEAR_W { w : self } } # [doc = "Bit 9 - Write 1 to clear BUS_RST_PENDING"]
+# [inline (always)]
+pub fn bus_rst_clear (& mut self) -> BUS_RST_CLEAR_W { BUS_RST_CLEAR_W { w : self } } # [doc = "Bit 13 - Write 1 to release the control endpoint lockout"]
+# [inline (always)]
+pub fn cel_release (& mut self) -> CEL_RELEASE_W { CEL_RELEASE_W { w : self } } } } # [doc = r"Register block"]
+# [repr (C)]
+pub struct EP { # [doc = "0x00 - OUT (host to device) endpoint status and configuration"]
+pub out_status : self :: ep :: OUT_STATUS , _reserved1 : [u8 ; 12usize]
+, # [doc = "0x10 - OUT buffer descriptor 0 state and length"]
+pub out_bd0_csr : self :: ep :: OUT_BD0_CSR , # [doc = "0x14 - OUT buffer descriptor 0 byte offset in the endpoint buffer"]
+pub out_bd0_ptr : self :: ep :: OUT_BD0_PTR , # [doc = "0x18 - OUT buffer descriptor 1 state and length"]
+pub out_bd1_csr : self :: ep :: OUT_BD1_CSR , # [doc = "0x1c - OUT buffer descriptor 1 byte offset in the endpoint buffer"]
+pub out_bd1_ptr : self :: ep :: OUT_BD1_PTR , # [doc = "0x20 - IN (device to host) endpoint status and configuration"]
+pub in_status : self :: ep :: IN_STATUS , _reserved6 : [u8 ; 12usize]
+, # [doc = "0x30 - IN buffer descriptor 0 state and length"]
+pub in_bd0_csr : self :: ep :: IN_BD0_CSR , # [doc = "0x34 - IN buffer descriptor 0 byte offset in the endpoint buffer"]
+pub in_bd0_ptr : self :: ep :: IN_BD0_PTR , # [doc = "0x38 - IN buffer descriptor 1 state and length"]
+pub in_bd1_csr : self :: ep :: IN_BD1_CSR , # [doc = "0x3c - IN buffer descriptor 1 byte offset in the endpoint buffer"]
+pub in_bd1_ptr : self :: ep :: IN_BD1_PTR , } # [doc = r"Register block"]
+# [doc = "Endpoint pair registers"]
+pub mod ep { # [doc = "OUT (host to device) endpoint status and configuration\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [out_status](out_status) module"]
+pub type OUT_STATUS = crate :: Reg < u32 , _OUT_STATUS > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _OUT_STATUS ; # [doc = "`read()` method returns [out_status::R](out_status::R) reader structure"]
+impl crate :: Readable for OUT_STATUS { } # [doc = "`write(|w| ..)` method takes [out_status::W](out_status::W) writer structure"]
+impl crate :: Writable for OUT_STATUS { } # [doc = "OUT (host to device) endpoint status and configuration"]
+pub mod out_status { # [doc = "Reader of register OUT_STATUS"]
+pub type R = crate :: R < u32 , super :: OUT_STATUS > ; # [doc = "Writer for register OUT_STATUS"]
+pub type W = crate :: W < u32 , super :: OUT_STATUS > ; # [doc = "Register OUT_STATUS `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: OUT_STATUS { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `EP_TYPE`"]
+pub type EP_TYPE_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `EP_TYPE`"]
+pub struct EP_TYPE_W < 'a > { w : & 'a mut W , } impl < 'a > EP_TYPE_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x07) | ((value as u32) & 0x07) ; self . w } } # [doc = "Reader of field `BD_DUAL`"]
+pub type BD_DUAL_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BD_DUAL`"]
+pub struct BD_DUAL_W < 'a > { w : & 'a mut W , } impl < 'a > BD_DUAL_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 4)) | (((value as u32) & 0x01) << 4) ; self . w } } # [doc = "Reader of field `BD_CTRL`"]
+pub type BD_CTRL_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BD_CTRL`"]
+pub struct BD_CTRL_W < 'a > { w : & 'a mut W , } impl < 'a > BD_CTRL_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 5)) | (((value as u32) & 0x01) << 5) ; self . w } } # [doc = "Reader of field `BD_IDX`"]
+pub type BD_IDX_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BD_IDX`"]
+pub struct BD_IDX_W < 'a > { w : & 'a mut W , } impl < 'a > BD_IDX_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 6)) | (((value as u32) & 0x01) << 6) ; self . w } } # [doc = "Reader of field `DT`"]
+pub type DT_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `DT`"]
+pub struct DT_W < 'a > { w : & 'a mut W , } impl < 'a > DT_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 7)) | (((value as u32) & 0x01) << 7) ; self . w } } impl R { # [doc = "Bits 0:2 - Endpoint type, 0 none, 1 isochronous, 2 interrupt, 4 bulk, 6 control, bit 0 set on the other types halts the endpoint"]
+# [inline (always)]
+pub fn ep_type (& self) -> EP_TYPE_R { EP_TYPE_R :: new ((self . bits & 0x07) as u8) } # [doc = "Bit 4 - Use both buffer descriptors alternately"]
+# [inline (always)]
+pub fn bd_dual (& self) -> BD_DUAL_R { BD_DUAL_R :: new (((self . bits >> 4) & 0x01) != 0) } # [doc = "Bit 5 - Control mode, buffer descriptor 1 takes the SETUP packets and 0 the data"]
+# [inline (always)]
+pub fn bd_ctrl (& self) -> BD_CTRL_R { BD_CTRL_R :: new (((self . bits >> 5) & 0x01) != 0) } # [doc = "Bit 6 - Buffer descriptor of the next transfer"]
+# [inline (always)]
+pub fn bd_idx (& self) -> BD_IDX_R { BD_IDX_R :: new (((self . bits >> 6) & 0x01) != 0) } # [doc = "Bit 7 - Data toggle of the next transfer"]
+# [inline (always)]
+pub fn dt (& self) -> DT_R { DT_R :: new (((self . bits >> 7) & 0x01) != 0) } } impl W { # [doc = "Bits 0:2 - Endpoint type, 0 none, 1 isochronous, 2 interrupt, 4 bulk, 6 control, bit 0 set on the other types halts the endpoint"]
+# [inline (always)]
+pub fn ep_type (& mut self) -> EP_TYPE_W { EP_TYPE_W { w : self } } # [doc = "Bit 4 - Use both buffer descriptors alternately"]
+# [inline (always)]
+pub fn bd_dual (& mut self) -> BD_DUAL_W { BD_DUAL_W { w : self } } # [doc = "Bit 5 - Control mode, buffer descriptor 1 takes the SETUP packets and 0 the data"]
+# [inline (always)]
+pub fn bd_ctrl (& mut self) -> BD_CTRL_W { BD_CTRL_W { w : self } } # [doc = "Bit 6 - Buffer descriptor of the next transfer"]
+# [inline (always)]
+pub fn bd_idx (& mut self) -> BD_IDX_W { BD_IDX_W { w : self } } # [doc = "Bit 7 - Data toggle of the next transfer"]
+# [inline (always)]
+pub fn dt (& mut self) -> DT_W { DT_W { w : self } } } } # [doc = "OUT buffer descriptor 0 state and length\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [out_bd0_csr](out_bd0_csr) module"]
+pub type OUT_BD0_CSR = crate :: Reg < u32 , _OUT_BD0_CSR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _OUT_BD0_CSR ; # [doc = "`read()` method returns [out_bd0_csr::R](out_bd0_csr::R) reader structure"]
+impl crate :: Readable for OUT_BD0_CSR { } # [doc = "`write(|w| ..)` method takes [out_bd0_csr::W](out_bd0_csr::W) writer structure"]
+impl crate :: Writable for OUT_BD0_CSR { } # [doc = "OUT buffer descriptor 0 state and length"]
+pub mod out_bd0_csr { # [doc = "Reader of register OUT_BD0_CSR"]
+pub type R = crate :: R < u32 , super :: OUT_BD0_CSR > ; # [doc = "Writer for register OUT_BD0_CSR"]
+pub type W = crate :: W < u32 , super :: OUT_BD0_CSR > ; # [doc = "Register OUT_BD0_CSR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: OUT_BD0_CSR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `LEN`"]
+pub type LEN_R = crate :: R < u16 , u16 > ; # [doc = "Write proxy for field `LEN`"]
+pub struct LEN_W < 'a > { w : & 'a mut W , } impl < 'a > LEN_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u16) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x03ff) | ((value as u32) & 0x03ff) ; self . w } } # [doc = "Reader of field `SETUP`"]
+pub type SETUP_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `SETUP`"]
+pub struct SETUP_W < 'a > { w : & 'a mut W , } impl < 'a > SETUP_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 12)) | (((value as u32) & 0x01) << 12) ; self . w } } # [doc = "Reader of field `STATE`"]
+pub type STATE_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `STATE`"]
+pub struct STATE_W < 'a > { w : & 'a mut W , } impl < 'a > STATE_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x07 << 13)) | (((value as u32) & 0x07) << 13) ; self . w } } impl R { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& self) -> LEN_R { LEN_R :: new ((self . bits & 0x03ff) as u16) } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& self) -> SETUP_R { SETUP_R :: new (((self . bits >> 12) & 0x01) != 0) } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& self) -> STATE_R { STATE_R :: new (((self . bits >> 13) & 0x07) as u8) } } impl W { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& mut self) -> LEN_W { LEN_W { w : self } } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& mut self) -> SETUP_W { SETUP_W { w : self } } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& mut self) -> STATE_W { STATE_W { w : self } } } } # [doc = "OUT buffer descriptor 0 byte offset in the endpoint buffer\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [out_bd0_ptr](out_bd0_ptr) module"]
+pub type OUT_BD0_PTR = crate :: Reg < u32 , _OUT_BD0_PTR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _OUT_BD0_PTR ; # [doc = "`read()` method returns [out_bd0_ptr::R](out_bd0_ptr::R) reader structure"]
+impl crate :: Readable for OUT_BD0_PTR { } # [doc = "`write(|w| ..)` method takes [out_bd0_ptr::W](out_bd0_ptr::W) writer structure"]
+impl crate :: Writable for OUT_BD0_PTR { } # [doc = "OUT buffer descriptor 0 byte offset in the endpoint buffer"]
+pub mod out_bd0_ptr { # [doc = "Reader of register OUT_BD0_PTR"]
+pub type R = crate :: R < u32 , super :: OUT_BD0_PTR > ; # [doc = "Writer for register OUT_BD0_PTR"]
+pub type W = crate :: W < u32 , super :: OUT_BD0_PTR > ; # [doc = "Register OUT_BD0_PTR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: OUT_BD0_PTR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } impl R { } impl W { } } # [doc = "OUT buffer descriptor 1 state and length\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [out_bd1_csr](out_bd1_csr) module"]
+pub type OUT_BD1_CSR = crate :: Reg < u32 , _OUT_BD1_CSR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _OUT_BD1_CSR ; # [doc = "`read()` method returns [out_bd1_csr::R](out_bd1_csr::R) reader structure"]
+impl crate :: Readable for OUT_BD1_CSR { } # [doc = "`write(|w| ..)` method takes [out_bd1_csr::W](out_bd1_csr::W) writer structure"]
+impl crate :: Writable for OUT_BD1_CSR { } # [doc = "OUT buffer descriptor 1 state and length"]
+pub mod out_bd1_csr { # [doc = "Reader of register OUT_BD1_CSR"]
+pub type R = crate :: R < u32 , super :: OUT_BD1_CSR > ; # [doc = "Writer for register OUT_BD1_CSR"]
+pub type W = crate :: W < u32 , super :: OUT_BD1_CSR > ; # [doc = "Register OUT_BD1_CSR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: OUT_BD1_CSR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `LEN`"]
+pub type LEN_R = crate :: R < u16 , u16 > ; # [doc = "Write proxy for field `LEN`"]
+pub struct LEN_W < 'a > { w : & 'a mut W , } impl < 'a > LEN_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u16) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x03ff) | ((value as u32) & 0x03ff) ; self . w } } # [doc = "Reader of field `SETUP`"]
+pub type SETUP_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `SETUP`"]
+pub struct SETUP_W < 'a > { w : & 'a mut W , } impl < 'a > SETUP_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 12)) | (((value as u32) & 0x01) << 12) ; self . w } } # [doc = "Reader of field `STATE`"]
+pub type STATE_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `STATE`"]
+pub struct STATE_W < 'a > { w : & 'a mut W , } impl < 'a > STATE_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x07 << 13)) | (((value as u32) & 0x07) << 13) ; self . w } } impl R { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& self) -> LEN_R { LEN_R :: new ((self . bits & 0x03ff) as u16) } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& self) -> SETUP_R { SETUP_R :: new (((self . bits >> 12) & 0x01) != 0) } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& self) -> STATE_R { STATE_R :: new (((self . bits >> 13) & 0x07) as u8) } } impl W { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& mut self) -> LEN_W { LEN_W { w : self } } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& mut self) -> SETUP_W { SETUP_W { w : self } } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& mut self) -> STATE_W { STATE_W { w : self } } } } # [doc = "OUT buffer descriptor 1 byte offset in the endpoint buffer\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [out_bd1_ptr](out_bd1_ptr) module"]
+pub type OUT_BD1_PTR = crate :: Reg < u32 , _OUT_BD1_PTR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _OUT_BD1_PTR ; # [doc = "`read()` method returns [out_bd1_ptr::R](out_bd1_ptr::R) reader structure"]
+impl crate :: Readable for OUT_BD1_PTR { } # [doc = "`write(|w| ..)` method takes [out_bd1_ptr::W](out_bd1_ptr::W) writer structure"]
+impl crate :: Writable for OUT_BD1_PTR { } # [doc = "OUT buffer descriptor 1 byte offset in the endpoint buffer"]
+pub mod out_bd1_ptr { # [doc = "Reader of register OUT_BD1_PTR"]
+pub type R = crate :: R < u32 , super :: OUT_BD1_PTR > ; # [doc = "Writer for register OUT_BD1_PTR"]
+pub type W = crate :: W < u32 , super :: OUT_BD1_PTR > ; # [doc = "Register OUT_BD1_PTR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: OUT_BD1_PTR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } impl R { } impl W { } } # [doc = "IN (device to host) endpoint status and configuration\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [in_status](in_status) module"]
+pub type IN_STATUS = crate :: Reg < u32 , _IN_STATUS > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _IN_STATUS ; # [doc = "`read()` method returns [in_status::R](in_status::R) reader structure"]
+impl crate :: Readable for IN_STATUS { } # [doc = "`write(|w| ..)` method takes [in_status::W](in_status::W) writer structure"]
+impl crate :: Writable for IN_STATUS { } # [doc = "IN (device to host) endpoint status and configuration"]
+pub mod in_status { # [doc = "Reader of register IN_STATUS"]
+pub type R = crate :: R < u32 , super :: IN_STATUS > ; # [doc = "Writer for register IN_STATUS"]
+pub type W = crate :: W < u32 , super :: IN_STATUS > ; # [doc = "Register IN_STATUS `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: IN_STATUS { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `EP_TYPE`"]
+pub type EP_TYPE_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `EP_TYPE`"]
+pub struct EP_TYPE_W < 'a > { w : & 'a mut W , } impl < 'a > EP_TYPE_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x07) | ((value as u32) & 0x07) ; self . w } } # [doc = "Reader of field `BD_DUAL`"]
+pub type BD_DUAL_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BD_DUAL`"]
+pub struct BD_DUAL_W < 'a > { w : & 'a mut W , } impl < 'a > BD_DUAL_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 4)) | (((value as u32) & 0x01) << 4) ; self . w } } # [doc = "Reader of field `BD_CTRL`"]
+pub type BD_CTRL_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BD_CTRL`"]
+pub struct BD_CTRL_W < 'a > { w : & 'a mut W , } impl < 'a > BD_CTRL_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 5)) | (((value as u32) & 0x01) << 5) ; self . w } } # [doc = "Reader of field `BD_IDX`"]
+pub type BD_IDX_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `BD_IDX`"]
+pub struct BD_IDX_W < 'a > { w : & 'a mut W , } impl < 'a > BD_IDX_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 6)) | (((value as u32) & 0x01) << 6) ; self . w } } # [doc = "Reader of field `DT`"]
+pub type DT_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `DT`"]
+pub struct DT_W < 'a > { w : & 'a mut W , } impl < 'a > DT_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 7)) | (((value as u32) & 0x01) << 7) ; self . w } } impl R { # [doc = "Bits 0:2 - Endpoint type, 0 none, 1 isochronous, 2 interrupt, 4 bulk, 6 control, bit 0 set on the other types halts the endpoint"]
+# [inline (always)]
+pub fn ep_type (& self) -> EP_TYPE_R { EP_TYPE_R :: new ((self . bits & 0x07) as u8) } # [doc = "Bit 4 - Use both buffer descriptors alternately"]
+# [inline (always)]
+pub fn bd_dual (& self) -> BD_DUAL_R { BD_DUAL_R :: new (((self . bits >> 4) & 0x01) != 0) } # [doc = "Bit 5 - Control mode, buffer descriptor 1 takes the SETUP packets and 0 the data"]
+# [inline (always)]
+pub fn bd_ctrl (& self) -> BD_CTRL_R { BD_CTRL_R :: new (((self . bits >> 5) & 0x01) != 0) } # [doc = "Bit 6 - Buffer descriptor of the next transfer"]
+# [inline (always)]
+pub fn bd_idx (& self) -> BD_IDX_R { BD_IDX_R :: new (((self . bits >> 6) & 0x01) != 0) } # [doc = "Bit 7 - Data toggle of the next transfer"]
+# [inline (always)]
+pub fn dt (& self) -> DT_R { DT_R :: new (((self . bits >> 7) & 0x01) != 0) } } impl W { # [doc = "Bits 0:2 - Endpoint type, 0 none, 1 isochronous, 2 interrupt, 4 bulk, 6 control, bit 0 set on the other types halts the endpoint"]
+# [inline (always)]
+pub fn ep_type (& mut self) -> EP_TYPE_W { EP_TYPE_W { w : self } } # [doc = "Bit 4 - Use both buffer descriptors alternately"]
+# [inline (always)]
+pub fn bd_dual (& mut self) -> BD_DUAL_W { BD_DUAL_W { w : self } } # [doc = "Bit 5 - Control mode, buffer descriptor 1 takes the SETUP packets and 0 the data"]
+# [inline (always)]
+pub fn bd_ctrl (& mut self) -> BD_CTRL_W { BD_CTRL_W { w : self } } # [doc = "Bit 6 - Buffer descriptor of the next transfer"]
+# [inline (always)]
+pub fn bd_idx (& mut self) -> BD_IDX_W { BD_IDX_W { w : self } } # [doc = "Bit 7 - Data toggle of the next transfer"]
+# [inline (always)]
+pub fn dt (& mut self) -> DT_W { DT_W { w : self } } } } # [doc = "IN buffer descriptor 0 state and length\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [in_bd0_csr](in_bd0_csr) module"]
+pub type IN_BD0_CSR = crate :: Reg < u32 , _IN_BD0_CSR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _IN_BD0_CSR ; # [doc = "`read()` method returns [in_bd0_csr::R](in_bd0_csr::R) reader structure"]
+impl crate :: Readable for IN_BD0_CSR { } # [doc = "`write(|w| ..)` method takes [in_bd0_csr::W](in_bd0_csr::W) writer structure"]
+impl crate :: Writable for IN_BD0_CSR { } # [doc = "IN buffer descriptor 0 state and length"]
+pub mod in_bd0_csr { # [doc = "Reader of register IN_BD0_CSR"]
+pub type R = crate :: R < u32 , super :: IN_BD0_CSR > ; # [doc = "Writer for register IN_BD0_CSR"]
+pub type W = crate :: W < u32 , super :: IN_BD0_CSR > ; # [doc = "Register IN_BD0_CSR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: IN_BD0_CSR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `LEN`"]
+pub type LEN_R = crate :: R < u16 , u16 > ; # [doc = "Write proxy for field `LEN`"]
+pub struct LEN_W < 'a > { w : & 'a mut W , } impl < 'a > LEN_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u16) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x03ff) | ((value as u32) & 0x03ff) ; self . w } } # [doc = "Reader of field `SETUP`"]
+pub type SETUP_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `SETUP`"]
+pub struct SETUP_W < 'a > { w : & 'a mut W , } impl < 'a > SETUP_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 12)) | (((value as u32) & 0x01) << 12) ; self . w } } # [doc = "Reader of field `STATE`"]
+pub type STATE_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `STATE`"]
+pub struct STATE_W < 'a > { w : & 'a mut W , } impl < 'a > STATE_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x07 << 13)) | (((value as u32) & 0x07) << 13) ; self . w } } impl R { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& self) -> LEN_R { LEN_R :: new ((self . bits & 0x03ff) as u16) } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& self) -> SETUP_R { SETUP_R :: new (((self . bits >> 12) & 0x01) != 0) } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& self) -> STATE_R { STATE_R :: new (((self . bits >> 13) & 0x07) as u8) } } impl W { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& mut self) -> LEN_W { LEN_W { w : self } } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& mut self) -> SETUP_W { SETUP_W { w : self } } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& mut self) -> STATE_W { STATE_W { w : self } } } } # [doc = "IN buffer descriptor 0 byte offset in the endpoint buffer\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [in_bd0_ptr](in_bd0_ptr) module"]
+pub type IN_BD0_PTR = crate :: Reg < u32 , _IN_BD0_PTR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _IN_BD0_PTR ; # [doc = "`read()` method returns [in_bd0_ptr::R](in_bd0_ptr::R) reader structure"]
+impl crate :: Readable for IN_BD0_PTR { } # [doc = "`write(|w| ..)` method takes [in_bd0_ptr::W](in_bd0_ptr::W) writer structure"]
+impl crate :: Writable for IN_BD0_PTR { } # [doc = "IN buffer descriptor 0 byte offset in the endpoint buffer"]
+pub mod in_bd0_ptr { # [doc = "Reader of register IN_BD0_PTR"]
+pub type R = crate :: R < u32 , super :: IN_BD0_PTR > ; # [doc = "Writer for register IN_BD0_PTR"]
+pub type W = crate :: W < u32 , super :: IN_BD0_PTR > ; # [doc = "Register IN_BD0_PTR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: IN_BD0_PTR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } impl R { } impl W { } } # [doc = "IN buffer descriptor 1 state and length\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [in_bd1_csr](in_bd1_csr) module"]
+pub type IN_BD1_CSR = crate :: Reg < u32 , _IN_BD1_CSR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _IN_BD1_CSR ; # [doc = "`read()` method returns [in_bd1_csr::R](in_bd1_csr::R) reader structure"]
+impl crate :: Readable for IN_BD1_CSR { } # [doc = "`write(|w| ..)` method takes [in_bd1_csr::W](in_bd1_csr::W) writer structure"]
+impl crate :: Writable for IN_BD1_CSR { } # [doc = "IN buffer descriptor 1 state and length"]
+pub mod in_bd1_csr { # [doc = "Reader of register IN_BD1_CSR"]
+pub type R = crate :: R < u32 , super :: IN_BD1_CSR > ; # [doc = "Writer for register IN_BD1_CSR"]
+pub type W = crate :: W < u32 , super :: IN_BD1_CSR > ; # [doc = "Register IN_BD1_CSR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: IN_BD1_CSR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } # [doc = "Reader of field `LEN`"]
+pub type LEN_R = crate :: R < u16 , u16 > ; # [doc = "Write proxy for field `LEN`"]
+pub struct LEN_W < 'a > { w : & 'a mut W , } impl < 'a > LEN_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u16) -> & 'a mut W { self . w . bits = (self . w . bits & ! 0x03ff) | ((value as u32) & 0x03ff) ; self . w } } # [doc = "Reader of field `SETUP`"]
+pub type SETUP_R = crate :: R < bool , bool > ; # [doc = "Write proxy for field `SETUP`"]
+pub struct SETUP_W < 'a > { w : & 'a mut W , } impl < 'a > SETUP_W < 'a > { # [doc = r"Sets the field bit"]
+# [inline (always)]
+pub fn set_bit (self) -> & 'a mut W { self . bit (true) } # [doc = r"Clears the field bit"]
+# [inline (always)]
+pub fn clear_bit (self) -> & 'a mut W { self . bit (false) } # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub fn bit (self , value : bool) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x01 << 12)) | (((value as u32) & 0x01) << 12) ; self . w } } # [doc = "Reader of field `STATE`"]
+pub type STATE_R = crate :: R < u8 , u8 > ; # [doc = "Write proxy for field `STATE`"]
+pub struct STATE_W < 'a > { w : & 'a mut W , } impl < 'a > STATE_W < 'a > { # [doc = r"Writes raw bits to the field"]
+# [inline (always)]
+pub unsafe fn bits (self , value : u8) -> & 'a mut W { self . w . bits = (self . w . bits & ! (0x07 << 13)) | (((value as u32) & 0x07) << 13) ; self . w } } impl R { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& self) -> LEN_R { LEN_R :: new ((self . bits & 0x03ff) as u16) } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& self) -> SETUP_R { SETUP_R :: new (((self . bits >> 12) & 0x01) != 0) } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& self) -> STATE_R { STATE_R :: new (((self . bits >> 13) & 0x07) as u8) } } impl W { # [doc = "Bits 0:9 - Length of the buffer, or of the data once done"]
+# [inline (always)]
+pub fn len (& mut self) -> LEN_W { LEN_W { w : self } } # [doc = "Bit 12 - Set when the received packet was a SETUP"]
+# [inline (always)]
+pub fn setup (& mut self) -> SETUP_W { SETUP_W { w : self } } # [doc = "Bits 13:15 - 0 none, 2 ready for data, 3 ready to stall, 4 done ok, 5 done with an error"]
+# [inline (always)]
+pub fn state (& mut self) -> STATE_W { STATE_W { w : self } } } } # [doc = "IN buffer descriptor 1 byte offset in the endpoint buffer\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [in_bd1_ptr](in_bd1_ptr) module"]
+pub type IN_BD1_PTR = crate :: Reg < u32 , _IN_BD1_PTR > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _IN_BD1_PTR ; # [doc = "`read()` method returns [in_bd1_ptr::R](in_bd1_ptr::R) reader structure"]
+impl crate :: Readable for IN_BD1_PTR { } # [doc = "`write(|w| ..)` method takes [in_bd1_ptr::W](in_bd1_ptr::W) writer structure"]
+impl crate :: Writable for IN_BD1_PTR { } # [doc = "IN buffer descriptor 1 byte offset in the endpoint buffer"]
+pub mod in_bd1_ptr { # [doc = "Reader of register IN_BD1_PTR"]
+pub type R = crate :: R < u32 , super :: IN_BD1_PTR > ; # [doc = "Writer for register IN_BD1_PTR"]
+pub type W = crate :: W < u32 , super :: IN_BD1_PTR > ; # [doc = "Register IN_BD1_PTR `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: IN_BD1_PTR { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } impl R { } impl W { } } } } # [doc = "USB endpoint buffers"]
+pub struct USB_DATA { _marker : PhantomData < * const () > } unsafe impl Send for USB_DATA { } impl USB_DATA { # [doc = r"Returns a pointer to the register block"]
+# [inline (always)]
+pub const fn ptr () -> * const usb_data :: RegisterBlock { 0x8500_0000 as * const _ } } impl Deref for USB_DATA { type Target = usb_data :: RegisterBlock ; # [inline (always)]
+fn deref (& self) -> & Self :: Target { unsafe { & * USB_DATA :: ptr () } } } # [doc = "USB endpoint buffers"]
+pub mod usb_data { # [doc = r"Register block"]
+# [repr (C)]
+pub struct RegisterBlock { # [doc = "0x00 - Endpoint buffer word, writes fill the IN buffer and reads return the OUT buffer"]
+pub data : [DATA ; 512]
+, } # [doc = "Endpoint buffer word, writes fill the IN buffer and reads return the OUT buffer\n\nThis register you can [`read`](crate::generic::Reg::read), [`reset`](crate::generic::Reg::reset), [`write`](crate::generic::Reg::write), [`write_with_zero`](crate::generic::Reg::write_with_zero), [`modify`](crate::generic::Reg::modify). See [API](https://docs.rs/svd2rust/#read--modify--write-api).\n\nFor information about available fields see [data](data) module"]
+pub type DATA = crate :: Reg < u32 , _DATA > ; # [allow (missing_docs)]
+# [doc (hidden)]
+pub struct _DATA ; # [doc = "`read()` method returns [data::R](data::R) reader structure"]
+impl crate :: Readable for DATA { } # [doc = "`write(|w| ..)` method takes [data::W](data::W) writer structure"]
+impl crate :: Writable for DATA { } # [doc = "Endpoint buffer word, writes fill the IN buffer and reads return the OUT buffer"]
+pub mod data { # [doc = "Reader of register DATA"]
+pub type R = crate :: R < u32 , super :: DATA > ; # [doc = "Writer for register DATA"]
+pub type W = crate :: W < u32 , super :: DATA > ; # [doc = "Register DATA `reset()`'s with value 0"]
+impl crate :: ResetValue for super :: DATA { type Type = u32 ; # [inline (always)]
+fn reset_value () -> Self :: Type { 0 } } impl R { } impl W { } } } # [no_mangle]
 static mut DEVICE_PERIPHERALS : bool = false ; # [doc = r"All the peripherals"]
 # [allow (non_snake_case)]
 pub struct Peripherals { # [doc = "UART"]
@@ -874,8 +1417,10 @@ pub RGBLED : RGBLED , # [doc = "LEDSTR"]
 pub LEDSTR : LEDSTR , # [doc = "TIMER"]
 pub TIMER : TIMER , # [doc = "JOY"]
 pub JOY : JOY , # [doc = "I2C"]
-pub I2C : I2C , } impl Peripherals { # [doc = r"Returns all the peripherals *once*"]
+pub I2C : I2C , # [doc = "USB core control"]
+pub USB_CORE : USB_CORE , # [doc = "USB endpoint buffers"]
+pub USB_DATA : USB_DATA , } impl Peripherals { # [doc = r"Returns all the peripherals *once*"]
 # [inline]
 pub fn take () -> Option < Self > { riscv :: interrupt :: free (| _ | { if unsafe { DEVICE_PERIPHERALS } { None } else { Some (unsafe { Peripherals :: steal () }) } }) } # [doc = r"Unchecked version of `Peripherals::take`"]
 # [inline]
-pub unsafe fn steal () -> Self { DEVICE_PERIPHERALS = true ; Peripherals { UART : UART { _marker : PhantomData } , RGBLED : RGBLED { _marker : PhantomData } , LEDSTR : LEDSTR { _marker : PhantomData } , TIMER : TIMER { _marker : PhantomData } , JOY : JOY { _marker : PhantomData } , I2C : I2C { _marker : PhantomData } , } } }
+pub unsafe fn steal () -> Self { DEVICE_PERIPHERALS = true ; Peripherals { UART : UART { _marker : PhantomData } , RGBLED : RGBLED { _marker : PhantomData } , LEDSTR : LEDSTR { _marker : PhantomData } , TIMER : TIMER { _marker : PhantomData } , JOY : JOY { _marker : PhantomData } , I2C : I2C { _marker : PhantomData } , USB_CORE : USB_CORE { _marker : PhantomData } , USB_DATA : USB_DATA { _marker : PhantomData } , } } }
@@ -24,26 +24,28 @@
 
 use super::led_string::{LEDString, LED, hsv_rainbow};
 use super::utils::sinu8;
-#[cfg(feature = "icetwanghw")]
-use crate::print;
-use crate::twang::rand::{random8, random8lim};
+use super::rand::{random8, random8lim};
 
 const DOTSPEED: u32 = 11;
 const DOTS_IN_BOWLS_COUNT: u32 = 3;
-const DOT_DISTANCE: u32 = 65535 / DOTS_IN_BOWLS_COUNT as u32;
+const DOT_DISTANCE: u32 = 65535 / DOTS_IN_BOWLS_COUNT;
 const DOT_BRIGHTNESS: u8 = 255;
 
+#[derive(Clone)]
 pub struct Attract {
+    pub mode: u32,  // Animation currently shown
 }
 
 impl Attract {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            mode: 0,
+        }
     }
 
     pub fn tick(&mut self, led_string: &mut LEDString, time: u32) {
         let mode = (time / 5000) % 6;
-        print!("Mode {} ", mode);
+        self.mode = mode;
         //let mode = 3;
 
         match mode {
@@ -83,7 +85,7 @@ impl Attract {
                     // println!("i {} mm {:#010X} mm16 {:#06X} mmf {:.4} nsin {:2.4}, n {:03}, c {:#04X}", i, mm, mm16, mmf, nsin, n, c);
                     led_string[n - 2] += LED::new(hsv_rainbow(c, 255, DOT_BRIGHTNESS / 4));
                     led_string[n - 1] += LED::new(hsv_rainbow(c, 255, DOT_BRIGHTNESS / 2));
-                    led_string[n + 0] += LED::new(hsv_rainbow(c, 255, DOT_BRIGHTNESS));
+                    led_string[n] += LED::new(hsv_rainbow(c, 255, DOT_BRIGHTNESS));
                     led_string[n + 1] += LED::new(hsv_rainbow(c, 255, DOT_BRIGHTNESS / 2));
                     led_string[n + 2] += LED::new(hsv_rainbow(c, 255, DOT_BRIGHTNESS / 4));
                 }
@@ -93,12 +95,11 @@ impl Attract {
                 led_string.nscale8(128);
 
                 let c = (time % 800) as u8;
-                let n;
-                if c < 240 {
-                    n = 121 - c / 2;
+                let n = if c < 240 {
+                    121 - c / 2
                 } else {
-                    n = 1;
-                }
+                    1
+                };
 
                 for i in 0..led_string.len() {
                     if random8() <= n {
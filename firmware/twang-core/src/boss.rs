@@ -0,0 +1,516 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::{enemy::{Enemy, EnemyKind}, led_string::LEDString, player::Player, rand::random8lim, spawner::Spawner, utils::sini8};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+use super::world::{scale_period, scale_speed};
+
+const BOSS_HIT_DUR: u32 = 600; // Flash and invulnerability time after a hit
+
+#[derive(Copy, Clone)]
+pub enum BossMove {
+    Static,
+    Wobble{range: i32, speed: i32},         // Swing around the phase position
+    Patrol{min: i32, max: i32, speed: i32}, // Walk back and forth between min and max
+}
+
+#[derive(Copy, Clone)]
+pub enum BossAttack {
+    None,
+    Projectile{rate: u32, speed: i32},          // Shoot enemies towards the player
+    Lava{reach: i32, ontime: u32, offtime: u32}, // Breathe lava out of both sides
+}
+
+/// One boss life. The boss goes through its phases in order, one per hit.
+#[derive(Copy, Clone)]
+pub struct BossPhase {
+    pub position: i32,
+    pub width: i32,
+    pub color: [u8; 3],
+    pub hit_color: [u8; 3],
+    pub movement: BossMove,
+    pub attack: BossAttack,
+    pub spawn_rate: u32,        // Side spawner rate in ms, 0 disables the spawners
+    pub spawn_speed: i32,       // Speed of the spawned enemies moving away from the boss
+    pub spawn_kind: EnemyKind,
+}
+
+/// The original TWANG boss, moving to a new spot and spawning faster on every hit.
+pub const BOSS_DUNGEON: [BossPhase; 3] = [
+    BossPhase {
+        position: 800, width: 40, color: [0x37, 0x00, 0x00], hit_color: [255, 255, 255],
+        movement: BossMove::Static,
+        attack: BossAttack::None,
+        spawn_rate: 1800, spawn_speed: 3, spawn_kind: EnemyKind::Basic,
+    },
+    BossPhase {
+        position: 200, width: 40, color: [0x37, 0x00, 0x00], hit_color: [255, 255, 255],
+        movement: BossMove::Static,
+        attack: BossAttack::None,
+        spawn_rate: 1600, spawn_speed: 3, spawn_kind: EnemyKind::Basic,
+    },
+    BossPhase {
+        position: 600, width: 40, color: [0x37, 0x00, 0x00], hit_color: [255, 255, 255],
+        movement: BossMove::Static,
+        attack: BossAttack::None,
+        spawn_rate: 1000, spawn_speed: 3, spawn_kind: EnemyKind::Basic,
+    },
+];
+
+/// A fire boss that walks around, shoots and finally breathes lava.
+pub const BOSS_FIRE: [BossPhase; 3] = [
+    BossPhase {
+        position: 700, width: 40, color: [0x40, 0x10, 0x00], hit_color: [255, 255, 255],
+        movement: BossMove::Patrol{min: 550, max: 850, speed: 1},
+        attack: BossAttack::None,
+        spawn_rate: 2000, spawn_speed: 3, spawn_kind: EnemyKind::Basic,
+    },
+    BossPhase {
+        position: 200, width: 40, color: [0x40, 0x10, 0x00], hit_color: [255, 255, 255],
+        movement: BossMove::Patrol{min: 150, max: 450, speed: 1},
+        attack: BossAttack::Projectile{rate: 2500, speed: 4},
+        spawn_rate: 1600, spawn_speed: 3, spawn_kind: EnemyKind::Basic,
+    },
+    BossPhase {
+        position: 600, width: 30, color: [0x50, 0x00, 0x10], hit_color: [255, 255, 255],
+        movement: BossMove::Wobble{range: 150, speed: 2},
+        attack: BossAttack::Lava{reach: 60, ontime: 1000, offtime: 2000},
+        spawn_rate: 1000, spawn_speed: 3, spawn_kind: EnemyKind::Basic,
+    },
+];
+
+/// The bosses there are, snapshots save the kind instead of the phases.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BossKind {
+    Dungeon,
+    Fire,
+}
+
+impl BossKind {
+    pub fn phases(self) -> &'static [BossPhase] {
+        match self {
+            BossKind::Dungeon => &BOSS_DUNGEON,
+            BossKind::Fire => &BOSS_FIRE,
+        }
+    }
+
+    fn from_u8(value: u8) -> Result<Self, SnapshotError> {
+        match value {
+            0 => Ok(BossKind::Dungeon),
+            1 => Ok(BossKind::Fire),
+            _ => Err(SnapshotError::Invalid),
+        }
+    }
+}
+
+impl Save for BossKind {
+    fn save(&self, w: &mut Writer) {
+        w.u8(*self as u8);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = BossKind::from_u8(r.u8()?)?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Boss {
+    pub position: i32,
+    pub start: i32,
+    pub stop: i32,
+    kind: Option<BossKind>, // None before the first spawn
+    phase: usize,
+    phase_millis: u32,  // Time the current phase started
+    hit_millis: u32,    // Time of the last hit
+    attack_millis: u32, // Time of the last projectile
+    velocity: i32,      // Patrol direction and speed
+    difficulty: u32,    // Spawn rate and projectile speed scaling in percent
+    pub lava_on: bool,
+    pub alive: bool,
+    pub defeated: bool,
+}
+
+impl Boss {
+    pub fn new() -> Self {
+        Self {
+            position: 0,
+            start: 0,
+            stop: 0,
+            kind: None,
+            phase: 0,
+            phase_millis: 0,
+            hit_millis: 0,
+            attack_millis: 0,
+            velocity: 0,
+            difficulty: 100,
+            lava_on: false,
+            alive: false,
+            defeated: false,
+        }
+    }
+
+    fn phases(&self) -> &'static [BossPhase] {
+        self.kind.map_or(&[], BossKind::phases)
+    }
+
+    fn current(&self) -> &BossPhase {
+        &self.phases()[self.phase]
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString, time: u32) {
+        if !self.alive {
+            return;
+        }
+        let phase = self.current();
+        if self.lava_on {
+            if let BossAttack::Lava{reach, ..} = phase.attack {
+                let start = led_string.vtor(self.start - reach);
+                let stop = led_string.vtor(self.stop + reach) + 1;
+                for i in start..stop {
+                    if random8lim(30) < 29 {
+                        led_string[i].set_rgb([150, 0, 0]);
+                    } else {
+                        led_string[i].set_rgb([180, 100, 0]);
+                    }
+                }
+            }
+        }
+        let color = if self.hit_n(time) && (time / 50).is_multiple_of(2) {phase.hit_color} else {phase.color};
+        let start = led_string.vtor(self.start);
+        let stop = led_string.vtor(self.stop) + 1;
+        for i in start..stop {
+            led_string[i].set_rgb(color);
+        }
+    }
+
+    pub fn tick(&mut self, time: u32, player_position: i32, spawners: &mut [Spawner], enemies: &mut [Enemy]) {
+        if !self.alive {
+            return;
+        }
+        let phase = *self.current();
+
+        match phase.movement {
+            BossMove::Static => (),
+            BossMove::Wobble{range, speed} => {
+                self.position = phase.position + ((sini8(((((time - self.phase_millis) / 37) as i32 * speed) & 0xFF) as i8) as i32) * range) / 255;
+            },
+            BossMove::Patrol{min, max, ..} => {
+                self.position += self.velocity;
+                if self.position <= min {
+                    self.position = min;
+                    self.velocity = self.velocity.abs();
+                } else if self.position >= max {
+                    self.position = max;
+                    self.velocity = -self.velocity.abs();
+                }
+            },
+        }
+        self.start = self.position - (phase.width / 2);
+        self.stop = self.position + (phase.width / 2);
+        if spawners.len() >= 2 {
+            spawners[0].move_to(self.start);
+            spawners[1].move_to(self.stop);
+        }
+
+        match phase.attack {
+            BossAttack::None => (),
+            BossAttack::Projectile{rate, speed} => {
+                if self.attack_millis + scale_period(rate, self.difficulty) < time {
+                    let speed = scale_speed(speed, self.difficulty);
+                    let (position, speed) = if player_position < self.start {
+                        (self.start - 1, -speed)
+                    } else {
+                        (self.stop + 1, speed)
+                    };
+                    for enemy in enemies.iter_mut() {
+                        if enemy.alive { continue }
+                        else {
+                            enemy.spawn(position, speed, 0, EnemyKind::Basic);
+                            break;
+                        }
+                    }
+                    self.attack_millis = time;
+                }
+            },
+            BossAttack::Lava{ontime, offtime, ..} => {
+                self.lava_on = (time - self.phase_millis) % (ontime + offtime) >= offtime;
+            },
+        }
+    }
+
+    /// Returns true if the position is inside the boss lava breath
+    pub fn lava_hit(&self, position: i32) -> bool {
+        match self.lava_reach() {
+            Some(reach) => {
+                ((position >= self.start - reach) && (position <= self.start)) ||
+                ((position >= self.stop) && (position <= self.stop + reach))
+            },
+            None => false
+        }
+    }
+
+    /// Reach of the lava breath while the boss breathes lava
+    pub fn lava_reach(&self) -> Option<i32> {
+        if !self.alive || !self.lava_on {
+            return None;
+        }
+        match self.current().attack {
+            BossAttack::Lava{reach, ..} => Some(reach),
+            _ => None
+        }
+    }
+
+    pub fn hits_left(&self) -> u32 {
+        if !self.alive {
+            return 0;
+        }
+        (self.phases().len() - self.phase) as u32
+    }
+
+    fn hit_n(&self, time: u32) -> bool {
+        self.hit_millis != 0 && time < self.hit_millis + BOSS_HIT_DUR
+    }
+
+    pub fn collide_player(&mut self, player: &Player, spawners: &mut [Spawner], time: u32) {
+        if !self.alive {
+            return;
+        }
+        if !player.attacking || self.hit_n(time) {
+            return;
+        }
+        let pmin = player.position - (player.attack_cur_width / 2);
+        let pmax = pmin + player.attack_cur_width;
+        let bmin = self.start;
+        let bmax = self.stop;
+        if ((pmax >= bmin) && (pmax <= bmax)) ||
+        ((pmin <= bmax) && (pmin >= bmin)) {
+            self.hit(spawners, time);
+        }
+    }
+
+    pub fn hit(&mut self, spawners: &mut [Spawner], time: u32) {
+        self.hit_millis = time;
+        self.phase += 1;
+        if self.phase >= self.phases().len() {
+            self.alive = false;
+            self.defeated = true;
+            for spawner in spawners.iter_mut() {
+                spawner.reset();
+            }
+        } else {
+            self.do_move(spawners, time);
+        }
+    }
+
+    pub fn do_move(&mut self, spawners: &mut [Spawner], time: u32) {
+        let phase = *self.current();
+        self.phase_millis = time;
+        self.attack_millis = time;
+        self.lava_on = false;
+        self.position = phase.position;
+        self.velocity = match phase.movement {
+            BossMove::Patrol{speed, ..} => speed,
+            _ => 0
+        };
+        self.start = self.position - (phase.width / 2);
+        self.stop = self.position + (phase.width / 2);
+        for spawner in spawners.iter_mut() {
+            spawner.reset();
+        }
+        if phase.spawn_rate != 0 && spawners.len() >= 2 {
+            let rate = scale_period(phase.spawn_rate, self.difficulty);
+            let speed = scale_speed(phase.spawn_speed, self.difficulty);
+            spawners[0].spawn(time, self.start, rate, -speed, 0, phase.spawn_kind);
+            spawners[1].spawn(time, self.stop, rate, speed, 0, phase.spawn_kind);
+        }
+    }
+
+    pub fn inspect(&self) -> Entity {
+        Entity::Boss {
+            position: self.position,
+            start: self.start,
+            stop: self.stop,
+            hits_left: self.hits_left(),
+            lava_on: self.lava_on,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.alive = false;
+        self.defeated = false;
+        self.lava_on = false;
+        self.hit_millis = 0;
+    }
+
+    pub fn spawn(&mut self, time: u32, kind: BossKind, difficulty: u32, spawners: &mut [Spawner]) {
+        self.kind = Some(kind);
+        self.difficulty = difficulty;
+        self.phase = 0;
+        self.hit_millis = 0;
+        self.alive = true;
+        self.do_move(spawners, time);
+    }
+}
+
+impl Save for Boss {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.kind.is_some());
+        self.kind.unwrap_or(BossKind::Dungeon).save(w);
+        w.i32(self.position);
+        w.i32(self.start);
+        w.i32(self.stop);
+        w.u32(self.phase as u32);
+        w.u32(self.phase_millis);
+        w.u32(self.hit_millis);
+        w.u32(self.attack_millis);
+        w.i32(self.velocity);
+        w.u32(self.difficulty);
+        w.bool(self.lava_on);
+        w.bool(self.alive);
+        w.bool(self.defeated);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        let spawned = r.bool()?;
+        let mut kind = BossKind::Dungeon;
+        kind.load(r)?;
+        self.kind = if spawned {Some(kind)} else {None};
+        self.position = r.i32()?;
+        self.start = r.i32()?;
+        self.stop = r.i32()?;
+        self.phase = r.u32()? as usize;
+        self.phase_millis = r.u32()?;
+        self.hit_millis = r.u32()?;
+        self.attack_millis = r.u32()?;
+        self.velocity = r.i32()?;
+        self.difficulty = r.u32()?;
+        self.lava_on = r.bool()?;
+        self.alive = r.bool()?;
+        self.defeated = r.bool()?;
+        // A live boss is always in one of its phases, scaling divides by the difficulty
+        if (self.alive && self.phase >= self.phases().len()) || self.difficulty == 0 {
+            return Err(SnapshotError::Invalid);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boss() -> (Boss, [Spawner; 2]) {
+        let mut spawners = [Spawner::new(); 2];
+        let mut boss = Boss::new();
+        boss.spawn(1000, BossKind::Dungeon, 100, &mut spawners);
+        (boss, spawners)
+    }
+
+    fn attack_boss(boss: &mut Boss, spawners: &mut [Spawner], time: u32) {
+        let mut player = Player::new(1);
+        player.spawn(boss.start - 10);
+        player.attack(true, time);
+        boss.collide_player(&player, spawners, time);
+    }
+
+    #[test]
+    fn goes_through_its_phases() {
+        let (mut boss, mut spawners) = boss();
+        assert_eq!(boss.hits_left(), 3);
+        assert_eq!(boss.position, BOSS_DUNGEON[0].position);
+        assert!(spawners[0].alive && spawners[1].alive);
+        attack_boss(&mut boss, &mut spawners, 2000);
+        assert_eq!(boss.hits_left(), 2);
+        assert_eq!(boss.position, BOSS_DUNGEON[1].position);
+        // Invulnerable right after a hit
+        attack_boss(&mut boss, &mut spawners, 2000 + BOSS_HIT_DUR - 1);
+        assert_eq!(boss.hits_left(), 2);
+        attack_boss(&mut boss, &mut spawners, 2000 + BOSS_HIT_DUR);
+        attack_boss(&mut boss, &mut spawners, 2000 + 2 * BOSS_HIT_DUR);
+        assert!(!boss.alive);
+        assert!(boss.defeated);
+        assert!(!spawners[0].alive && !spawners[1].alive);
+    }
+
+    #[test]
+    fn out_of_reach_attack_misses() {
+        let (mut boss, mut spawners) = boss();
+        let mut player = Player::new(1);
+        player.spawn(boss.start - player.attack_width);
+        player.attack(true, 2000);
+        boss.collide_player(&player, &mut spawners, 2000);
+        assert_eq!(boss.hits_left(), 3);
+    }
+
+    #[test]
+    fn breathes_lava() {
+        let mut spawners = [Spawner::new(); 2];
+        let mut boss = Boss::new();
+        boss.spawn(1000, BossKind::Fire, 100, &mut spawners);
+        let mut enemies = [Enemy::new(); 4];
+        attack_boss(&mut boss, &mut spawners, 2000);
+        attack_boss(&mut boss, &mut spawners, 3000);
+        let reach = match BOSS_FIRE[2].attack {
+            BossAttack::Lava{reach, ..} => reach,
+            _ => panic!("the last phase breathes lava"),
+        };
+        boss.tick(3100, 0, &mut spawners, &mut enemies);
+        assert!(!boss.lava_hit(boss.start - 1));
+        boss.tick(5100, 0, &mut spawners, &mut enemies);
+        assert!(boss.lava_hit(boss.start - 1));
+        assert!(boss.lava_hit(boss.stop + reach));
+        assert!(!boss.lava_hit(boss.stop + reach + 1));
+    }
+
+    #[test]
+    fn saves_which_boss_it_is() {
+        let mut spawners = [Spawner::new(); 2];
+        let mut boss = Boss::new();
+        boss.spawn(1000, BossKind::Fire, 100, &mut spawners);
+        let mut data = std::vec::Vec::new();
+        let mut out = |b| data.push(b);
+        let mut w = Writer::new(&mut out);
+        boss.save(&mut w);
+        w.finish();
+        let mut loaded = Boss::new();
+        let mut r = Reader::new(&data).unwrap();
+        loaded.load(&mut r).unwrap();
+        assert_eq!(loaded.kind, Some(BossKind::Fire));
+        assert_eq!(loaded.hits_left(), BOSS_FIRE.len() as u32);
+    }
+
+    #[test]
+    fn rejects_unknown_bosses() {
+        let mut data = std::vec::Vec::new();
+        let mut out = |b| data.push(b);
+        let mut w = Writer::new(&mut out);
+        w.bool(true);
+        w.u8(2);
+        w.finish();
+        let mut r = Reader::new(&data).unwrap();
+        assert_eq!(Boss::new().load(&mut r), Err(SnapshotError::Invalid));
+    }
+}
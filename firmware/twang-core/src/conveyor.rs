@@ -23,11 +23,13 @@
  */
 
 use super::{led_string::LEDString, utils::range_map};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
 //use crate::print;
 
 const CONVEYOR_BRIGHTNESS: u8 = 40;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct Conveyor {
     pub pos_start: i32,
     pub pos_end: i32,
@@ -63,6 +65,10 @@ impl Conveyor {
         }
     }
 
+    pub fn inspect(&self) -> Entity {
+        Entity::Conveyor{start: self.pos_start, end: self.pos_end, speed: self.speed}
+    }
+
     pub fn reset(&mut self) {
         self.alive = false;
     }
@@ -73,4 +79,28 @@ impl Conveyor {
         self.speed = speed;
         self.alive = true;
     }
-}
\ No newline at end of file
+}
+
+impl Save for Conveyor {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.pos_start);
+        w.i32(self.pos_end);
+        w.i32(self.speed);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.pos_start = r.i32()?;
+        self.pos_end = r.i32()?;
+        self.speed = r.i32()?;
+        Ok(())
+    }
+}
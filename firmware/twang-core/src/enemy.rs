@@ -0,0 +1,400 @@
+/*
+ * Copyright (c) 2020-2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::{lava::Lava, lava_flow::LavaFlow, led_string::LEDString, player::Player, portal::Portal, utils::{constrain, sini8}};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+
+const SHIELD_HITS: u8 = 1;
+const SHIELD_KNOCKBACK: i32 = 10;
+const INVISIBLE_RANGE: i32 = 120;
+const INVISIBLE_FLASH_DUR: u32 = 100;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EnemyKind {
+    Basic,      // Moves in a straight line or wobbles around its origin
+    Chaser,     // Homes in on the player
+    Shielded,   // Needs two hits to be killed
+    Splitter,   // Splits into two basic enemies when killed
+    Invisible,  // Only flashes up when the player is close
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Enemy {
+    pub position: i32,
+    prev_position: i32,
+    origin: i32,
+    speed: i32,
+    wobble: i32,
+    pub kind: EnemyKind,
+    shield: u8,
+    spared_attack: Option<u32>, // Start of an attack that already hit us, it can't kill us
+    pub alive: bool,
+    pub player_side: i32
+}
+
+impl Enemy {
+    pub fn new() -> Self {
+        Self {
+            position: 500,
+            prev_position: 500,
+            origin: 500,
+            speed: 0,
+            wobble: 0,
+            kind: EnemyKind::Basic,
+            shield: 0,
+            spared_attack: None,
+            alive: false,
+            player_side: 0,
+        }
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString, time: u32, player_position: i32) {
+        if !self.alive {
+            return;
+        }
+        let color = match self.kind {
+            EnemyKind::Basic => [255, 0, 0],
+            EnemyKind::Chaser => [255, 40, 0],
+            EnemyKind::Shielded => if self.shield > 0 {[255, 0, 120]} else {[255, 0, 0]},
+            EnemyKind::Splitter => [255, 160, 0],
+            EnemyKind::Invisible => {
+                if (self.position - player_position).abs() > INVISIBLE_RANGE ||
+                    (time / INVISIBLE_FLASH_DUR).is_multiple_of(2) {
+                    return;
+                }
+                [255, 80, 80]
+            },
+        };
+        let pos = led_string.vtor(self.position);
+        led_string[pos].set_rgb(color);
+    }
+
+    pub fn tick(&mut self, led_string: &LEDString, time: u32, player_position: i32) {
+        if !self.alive {
+            return;
+        }
+        self.prev_position = self.position;
+        if self.kind == EnemyKind::Chaser {
+            let step = self.speed.abs().min((player_position - self.position).abs());
+            if player_position > self.position {
+                self.position += step;
+            } else {
+                self.position -= step;
+            }
+        } else if self.wobble != 0 {
+            self.position = self.origin + ((sini8((((time / 37) as i32 * self.speed) & 0xFF) as i8) as i32) * self.wobble) / 255;
+        } else {
+            self.position += self.speed;
+            if self.position >= led_string.vlen() || self.position < 0 {
+                self.alive = false;
+            }
+        }
+    }
+
+    /// Returns true if the player killed this enemy
+    pub fn collide_player(&mut self, player: &Player) -> bool {
+        if !self.alive {
+            return false;
+        }
+        if self.player_side == 0 {
+            if self.position < player.position {
+                self.player_side = -1;
+            } else if self.position > player.position {
+                self.player_side = 1;
+            }
+            return false;
+        }
+        if player.attacking && self.spared_attack != Some(player.attacking_millis) {
+            let amin = player.position - (player.attack_cur_width / 2);
+            let amax = player.position + (player.attack_cur_width / 2);
+            if amin < self.position && self.position < amax {
+                if self.shield > 0 {
+                    // The shield takes the hit and we get pushed out of reach,
+                    // the next hit has to come from another attack
+                    self.shield -= 1;
+                    self.spared_attack = Some(player.attacking_millis);
+                    let pushed = constrain(player.position + self.player_side * (player.attack_cur_width / 2 + SHIELD_KNOCKBACK), 0, 999);
+                    self.origin += pushed - self.position;
+                    self.position = pushed;
+                    return false;
+                }
+                self.alive = false;
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn collide_lava(&mut self, lava: &Lava) {
+        if !self.alive || !lava.alive || !lava.state {
+            return;
+        }
+        if (self.position >= lava.pos_start) &&
+            (self.position < lava.pos_end) {
+            self.alive = false;
+        }
+    }
+
+    pub fn collide_lava_flow(&mut self, flow: &LavaFlow) {
+        if !self.alive || !flow.alive || !flow.hot {
+            return;
+        }
+        if (self.position >= flow.pos_start) &&
+            (self.position <= flow.pos_end) {
+            self.alive = false;
+        }
+    }
+
+    pub fn collide_portal(&mut self, portal: &Portal) {
+        if !self.alive || !portal.alive {
+            return;
+        }
+        if let Some(position) = portal.warp(self.prev_position, self.position) {
+            // Wobbling enemies keep wobbling around the other end
+            self.origin += position - self.position;
+            self.position = position;
+            self.prev_position = position;
+            // We may have ended up on the other side of the player
+            self.player_side = 0;
+        }
+    }
+
+    pub fn inspect(&self) -> Entity {
+        Entity::Enemy{position: self.position, speed: self.speed, kind: self.kind, player_side: self.player_side}
+    }
+
+    pub fn reset(&mut self) {
+        self.alive = false;
+    }
+
+    /// Makes us immune to the attack started at `attack`
+    pub fn spare(&mut self, attack: u32) {
+        self.spared_attack = Some(attack);
+    }
+
+    pub fn spawn(&mut self, position: i32, speed: i32, wobble: i32, kind: EnemyKind) {
+        self.alive = true;
+        self.kind = kind;
+        self.shield = if kind == EnemyKind::Shielded {SHIELD_HITS} else {0};
+        self.spared_attack = None;
+        self.position = position;
+        self.prev_position = position;
+        self.origin = position;
+        self.speed = speed;
+        self.wobble = wobble;
+        self.player_side = 0;
+    }
+}
+
+impl EnemyKind {
+    fn from_u8(value: u8) -> Result<Self, SnapshotError> {
+        match value {
+            0 => Ok(EnemyKind::Basic),
+            1 => Ok(EnemyKind::Chaser),
+            2 => Ok(EnemyKind::Shielded),
+            3 => Ok(EnemyKind::Splitter),
+            4 => Ok(EnemyKind::Invisible),
+            _ => Err(SnapshotError::Invalid),
+        }
+    }
+}
+
+impl Save for EnemyKind {
+    fn save(&self, w: &mut Writer) {
+        w.u8(*self as u8);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = EnemyKind::from_u8(r.u8()?)?;
+        Ok(())
+    }
+}
+
+impl Save for Enemy {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.position);
+        w.i32(self.prev_position);
+        w.i32(self.origin);
+        w.i32(self.speed);
+        w.i32(self.wobble);
+        self.kind.save(w);
+        w.u8(self.shield);
+        w.bool(self.spared_attack.is_some());
+        w.u32(self.spared_attack.unwrap_or(0));
+        w.i32(self.player_side);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.position = r.i32()?;
+        self.prev_position = r.i32()?;
+        self.origin = r.i32()?;
+        self.speed = r.i32()?;
+        self.wobble = r.i32()?;
+        self.kind.load(r)?;
+        self.shield = r.u8()?;
+        let spared = r.bool()?;
+        let attack = r.u32()?;
+        self.spared_attack = if spared {Some(attack)} else {None};
+        self.player_side = r.i32()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enemy_at(position: i32, kind: EnemyKind) -> Enemy {
+        let mut enemy = Enemy::new();
+        enemy.spawn(position, 0, 0, kind);
+        enemy
+    }
+
+    fn attacking_player(position: i32, time: u32) -> Player {
+        let mut player = Player::new(1);
+        player.spawn(position);
+        player.attack(true, time);
+        player
+    }
+
+    #[test]
+    fn picks_a_side_on_first_contact() {
+        let mut player = Player::new(1);
+        player.spawn(100);
+        let mut enemy = enemy_at(500, EnemyKind::Basic);
+        assert!(!enemy.collide_player(&player));
+        assert_eq!(enemy.player_side, 1);
+        let mut enemy = enemy_at(50, EnemyKind::Basic);
+        enemy.collide_player(&player);
+        assert_eq!(enemy.player_side, -1);
+    }
+
+    #[test]
+    fn killed_inside_the_attack() {
+        let player = attacking_player(500, 1000);
+        let reach = player.attack_cur_width / 2;
+        let mut enemy = enemy_at(500 + reach, EnemyKind::Basic);
+        enemy.player_side = 1;
+        assert!(!enemy.collide_player(&player));
+        assert!(enemy.alive);
+        enemy.position = 500 + reach - 1;
+        assert!(enemy.collide_player(&player));
+        assert!(!enemy.alive);
+    }
+
+    #[test]
+    fn shield_takes_one_hit() {
+        let player = attacking_player(500, 1000);
+        let mut enemy = enemy_at(510, EnemyKind::Shielded);
+        enemy.player_side = 1;
+        assert!(!enemy.collide_player(&player));
+        assert!(enemy.alive);
+        // Knocked out of reach of the running attack
+        assert!(enemy.position >= 500 + player.attack_cur_width / 2);
+        // Walking back into the same attack doesn't kill us
+        enemy.position = 510;
+        assert!(!enemy.collide_player(&player));
+        assert!(enemy.alive);
+        let strike = attacking_player(500, 2000);
+        assert!(enemy.collide_player(&strike));
+        assert!(!enemy.alive);
+    }
+
+    #[test]
+    fn spared_by_the_attack_that_split_us() {
+        let player = attacking_player(500, 1000);
+        let mut enemy = enemy_at(510, EnemyKind::Basic);
+        enemy.player_side = 1;
+        enemy.spare(player.attacking_millis);
+        assert!(!enemy.collide_player(&player));
+        assert!(enemy.alive);
+        let strike = attacking_player(500, 2000);
+        assert!(enemy.collide_player(&strike));
+    }
+
+    #[test]
+    fn dies_in_lava_and_flows() {
+        let mut lava = Lava::new();
+        lava.spawn(0, 400, 500, 1000, 1000, 0, true);
+        let mut enemy = enemy_at(450, EnemyKind::Basic);
+        enemy.collide_lava(&lava);
+        assert!(!enemy.alive);
+
+        let mut flow = LavaFlow::new();
+        flow.spawn(600, 20, 2, 500, 700);
+        let mut enemy = enemy_at(610, EnemyKind::Basic);
+        flow.hot = false;
+        enemy.collide_lava_flow(&flow);
+        assert!(enemy.alive);
+        flow.hot = true;
+        enemy.collide_lava_flow(&flow);
+        assert!(!enemy.alive);
+    }
+
+    #[test]
+    fn walks_off_the_strip() {
+        let led_string = LEDString::new(144);
+        let mut enemy = Enemy::new();
+        enemy.spawn(2, -2, 0, EnemyKind::Basic);
+        enemy.tick(&led_string, 0, 500);
+        assert!(enemy.alive);
+        enemy.tick(&led_string, 16, 500);
+        assert!(!enemy.alive);
+    }
+
+    #[test]
+    fn chaser_homes_in() {
+        let led_string = LEDString::new(144);
+        let mut enemy = Enemy::new();
+        enemy.spawn(500, 3, 0, EnemyKind::Chaser);
+        enemy.tick(&led_string, 0, 400);
+        assert_eq!(enemy.position, 497);
+        enemy.tick(&led_string, 16, 498);
+        assert_eq!(enemy.position, 498);
+    }
+
+    #[test]
+    fn warps_through_portals() {
+        let led_string = LEDString::new(144);
+        let mut portal = Portal::new();
+        portal.spawn(400, 750);
+        let mut enemy = Enemy::new();
+        enemy.spawn(752, -4, 0, EnemyKind::Basic);
+        enemy.player_side = 1;
+        enemy.tick(&led_string, 0, 100);
+        enemy.collide_portal(&portal);
+        assert_eq!(enemy.position, 398);
+        assert_eq!(enemy.player_side, 0);
+    }
+}
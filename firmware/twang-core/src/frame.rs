@@ -0,0 +1,206 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! LED frames on a serial line, for showing the simulator on a real strip.
+//!
+//! A frame is the magic, the LED count as u16, the RGB bytes of the LEDs in
+//! string order and an FNV-1a hash of the count and the colors, all little
+//! endian. The decoder skips anything between frames, like console echo, and
+//! drops damaged frames. A frame of no LEDs ends the stream.
+
+use crate::snapshot::{fnv1a, FNV_OFFSET};
+
+const MAGIC: [u8; 3] = *b"TWF";
+
+/// Streams a frame of `leds` out.
+pub fn encode(leds: &[[u8; 3]], out: &mut dyn FnMut(u8)) {
+    for byte in MAGIC {
+        out(byte);
+    }
+    let mut hash = FNV_OFFSET;
+    let mut put = |byte| {
+        hash = fnv1a(hash, byte);
+        out(byte);
+    };
+    for byte in (leds.len() as u16).to_le_bytes() {
+        put(byte);
+    }
+    for led in leds {
+        for byte in led {
+            put(*byte);
+        }
+    }
+    for byte in hash.to_le_bytes() {
+        out(byte);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Field {
+    Magic,
+    Length,
+    Color,
+    Hash,
+}
+
+/// Collects frames of up to `N` LEDs a byte at a time.
+pub struct Decoder<const N: usize> {
+    leds: [[u8; 3]; N],
+    len: usize,         // LEDs of the frame being received
+    shown: usize,       // LEDs of the last complete frame
+    field: Field,
+    pos: usize,         // Bytes received of the current field
+    value: u32,
+    hash: u32,
+}
+
+impl<const N: usize> Default for Decoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Decoder<N> {
+    pub fn new() -> Self {
+        Self {
+            leds: [[0; 3]; N],
+            len: 0,
+            shown: 0,
+            field: Field::Magic,
+            pos: 0,
+            value: 0,
+            hash: FNV_OFFSET,
+        }
+    }
+
+    /// Drops the frame being received, e.g. after lost bytes.
+    pub fn reset(&mut self) {
+        self.field = Field::Magic;
+        self.pos = 0;
+    }
+
+    /// LEDs of the frame `push` just completed, the next bytes overwrite them.
+    pub fn leds(&self) -> &[[u8; 3]] {
+        &self.leds[..self.shown]
+    }
+
+    /// Takes the next byte, true when it completed a frame.
+    pub fn push(&mut self, byte: u8) -> bool {
+        match self.field {
+            Field::Magic => {
+                if byte == MAGIC[self.pos] {
+                    self.next(Field::Magic, MAGIC.len(), Field::Length);
+                    self.hash = FNV_OFFSET;
+                    self.value = 0;
+                } else {
+                    // Could be the start of the real magic
+                    self.pos = (byte == MAGIC[0]) as usize;
+                }
+            }
+            Field::Length => {
+                self.hash = fnv1a(self.hash, byte);
+                self.value |= (byte as u32) << (8 * self.pos);
+                self.next(Field::Length, 2, Field::Color);
+                if self.field == Field::Color {
+                    self.len = self.value as usize;
+                    if self.len > N {
+                        self.reset();
+                    } else if self.len == 0 {
+                        self.field = Field::Hash;
+                    }
+                    self.value = 0;
+                }
+            }
+            Field::Color => {
+                self.hash = fnv1a(self.hash, byte);
+                self.leds[self.pos / 3][self.pos % 3] = byte;
+                self.next(Field::Color, self.len * 3, Field::Hash);
+            }
+            Field::Hash => {
+                self.value |= (byte as u32) << (8 * self.pos);
+                self.next(Field::Hash, 4, Field::Magic);
+                if self.field == Field::Magic && self.value == self.hash {
+                    self.shown = self.len;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Moves on to `then` after `len` bytes of `field`.
+    fn next(&mut self, field: Field, len: usize, then: Field) {
+        self.pos += 1;
+        if self.pos == len {
+            self.field = then;
+            self.pos = 0;
+        } else {
+            self.field = field;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framed(leds: &[[u8; 3]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        encode(leds, &mut |b| data.push(b));
+        data
+    }
+
+    /// Feeds `data`, returns the LEDs of every completed frame.
+    fn decode<const N: usize>(decoder: &mut Decoder<N>, data: &[u8]) -> Vec<Vec<[u8; 3]>> {
+        let mut frames = Vec::new();
+        for &byte in data {
+            if decoder.push(byte) {
+                frames.push(decoder.leds().to_vec());
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn skips_noise_between_frames() {
+        let leds = [[1, 2, 3], [0x54, 0x57, 0x46], [255, 0, 7]];
+        let mut data = b"TTW> frames\r\n".to_vec();
+        data.extend(framed(&leds));
+        data.extend(b"TWFjunk");
+        data.extend(framed(&[]));
+        let mut decoder = Decoder::<4>::new();
+        assert_eq!(decode(&mut decoder, &data), vec![leds.to_vec(), vec![]]);
+    }
+
+    #[test]
+    fn drops_damaged_and_oversized_frames() {
+        let mut damaged = framed(&[[9, 9, 9], [8, 8, 8]]);
+        damaged[6] ^= 0x10;
+        let mut data = damaged;
+        data.extend(framed(&[[1, 1, 1]; 5]));
+        data.extend(framed(&[[4, 5, 6]]));
+        let mut decoder = Decoder::<4>::new();
+        assert_eq!(decode(&mut decoder, &data), vec![vec![[4, 5, 6]]]);
+    }
+}
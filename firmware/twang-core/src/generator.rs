@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::input::LR_MAX;
+use super::enemy::EnemyKind;
+use super::rand::Rand16;
+use super::world::{scale_conveyor_speed, scale_period, World, CONVEYOR_POOL_COUNT, ENEMY_POOL_COUNT, LAVA_POOL_COUNT, SPAWNER_POOL_COUNT};
+
+const PLAYER_SPEED: i32 = LR_MAX;   // Player speed per frame with the joystick pushed
+const FRAME_TIME: u32 = 16;         // Frame time in ms
+const SAFE_ZONE: i32 = 100;         // Keep the start free of hazards
+const EXIT_ZONE: i32 = 980;         // Keep the exit free of lava
+const GENERATOR_ATTEMPTS: u32 = 20;
+
+#[derive(Clone, Copy)]
+struct EnemyDef {
+    position: i32,
+    speed: i32,
+    wobble: i32,
+}
+
+#[derive(Clone, Copy)]
+struct SpawnerDef {
+    position: i32,
+    rate: u32,
+    speed: i32,
+    activate: u32,
+}
+
+#[derive(Clone, Copy)]
+struct LavaDef {
+    pos_start: i32,
+    pos_end: i32,
+    ontime: u32,
+    offtime: u32,
+    offset: u32,
+}
+
+#[derive(Clone, Copy)]
+struct ConveyorDef {
+    pos_start: i32,
+    pos_end: i32,
+    speed: i32,
+}
+
+/// A generated level, built from the seeded RNG and checked to be solvable
+/// before it is handed to the world.
+pub struct LevelPlan {
+    enemies: [EnemyDef; ENEMY_POOL_COUNT],
+    enemy_count: usize,
+    spawners: [SpawnerDef; SPAWNER_POOL_COUNT],
+    spawner_count: usize,
+    lavas: [LavaDef; LAVA_POOL_COUNT],
+    lava_count: usize,
+    conveyors: [ConveyorDef; CONVEYOR_POOL_COUNT],
+    conveyor_count: usize,
+}
+
+impl LevelPlan {
+    fn empty() -> Self {
+        Self {
+            enemies: [EnemyDef{position: 0, speed: 0, wobble: 0}; ENEMY_POOL_COUNT],
+            enemy_count: 0,
+            spawners: [SpawnerDef{position: 0, rate: 0, speed: 0, activate: 0}; SPAWNER_POOL_COUNT],
+            spawner_count: 0,
+            lavas: [LavaDef{pos_start: 0, pos_end: 0, ontime: 0, offtime: 0, offset: 0}; LAVA_POOL_COUNT],
+            lava_count: 0,
+            conveyors: [ConveyorDef{pos_start: 0, pos_end: 0, speed: 0}; CONVEYOR_POOL_COUNT],
+            conveyor_count: 0,
+        }
+    }
+
+    /// Generates a level that is solvable in a world running at `scaling`
+    /// percent difficulty. The same seed and difficulty always result in the
+    /// same level.
+    pub fn generate(seed: u16, difficulty: u32, scaling: u32) -> Self {
+        let mut rng = Rand16::new(seed);
+        for _ in 0..GENERATOR_ATTEMPTS {
+            let plan = Self::random(&mut rng, difficulty);
+            if plan.solvable(scaling) {
+                return plan;
+            }
+        }
+        // Give up and fall back to the first level
+        let mut plan = Self::empty();
+        plan.enemies[0] = EnemyDef{position: 500, speed: 0, wobble: 0};
+        plan.enemy_count = 1;
+        plan
+    }
+
+    fn random(rng: &mut Rand16, difficulty: u32) -> Self {
+        let d = difficulty.min(40) as i32;
+        let mut plan = Self::empty();
+
+        // Conveyors
+        let mut cursor = SAFE_ZONE;
+        for _ in 0..rng.random8lim(CONVEYOR_POOL_COUNT as u8 + 1) {
+            let pos_start = cursor + rng.random16minlim(0, 200) as i32;
+            let pos_end = pos_start + rng.random16minlim(100, 400) as i32;
+            if pos_end > 998 {
+                break;
+            }
+            let speed = rng.random8minlim(1, 3 + (d / 5).min(5) as u8) as i32;
+            let speed = if rng.random8lim(2) == 0 {speed} else {-speed};
+            plan.conveyors[plan.conveyor_count] = ConveyorDef{pos_start, pos_end, speed};
+            plan.conveyor_count += 1;
+            cursor = pos_end;
+        }
+
+        // Lava
+        let mut cursor = SAFE_ZONE + 50;
+        for _ in 0..rng.random8lim((1 + d / 3).min(LAVA_POOL_COUNT as i32) as u8 + 1) {
+            let pos_start = cursor + rng.random16minlim(20, 200) as i32;
+            let pos_end = pos_start + rng.random16minlim(40, 60 + (d * 5).min(100) as u16) as i32;
+            if pos_end > EXIT_ZONE {
+                break;
+            }
+            let ontime = rng.random16minlim(1000, 2000) as u32;
+            let offtime = (2000 - (d as u32 * 50).min(1000)) + rng.random16minlim(0, 500) as u32;
+            let offset = rng.random16minlim(0, 2000) as u32;
+            plan.lavas[plan.lava_count] = LavaDef{pos_start, pos_end, ontime, offtime, offset};
+            plan.lava_count += 1;
+            cursor = pos_end + 50;
+        }
+
+        // Enemies, leave some room for the spawners
+        let count = (1 + d / 2).min(ENEMY_POOL_COUNT as i32 - 4);
+        for _ in 0..rng.random8minlim(1, count as u8 + 1) {
+            let position = rng.random16minlim(SAFE_ZONE as u16 + 100, 999) as i32;
+            let (speed, wobble) = match rng.random8lim(3) {
+                0 => (0, 0),
+                1 => (-(rng.random8minlim(1, 2 + (d / 5).min(3) as u8) as i32), 0),
+                _ => (rng.random8minlim(2, 4 + (d / 5).min(4) as u8) as i32, rng.random16minlim(100, 300) as i32),
+            };
+            plan.enemies[plan.enemy_count] = EnemyDef{position, speed, wobble};
+            plan.enemy_count += 1;
+        }
+
+        // Spawners only show up after the first few levels
+        if d >= 2 {
+            for _ in 0..rng.random8lim(SPAWNER_POOL_COUNT as u8 + 1) {
+                let position = if rng.random8lim(2) == 0 {999} else {rng.random16minlim(500, 999) as i32};
+                let rate = (4000 - (d as u32 * 150).min(2800)) + rng.random16minlim(0, 1000) as u32;
+                let speed = -(rng.random8minlim(2, 3 + (d / 4).min(3) as u8) as i32);
+                let activate = rng.random16minlim(0, 5000) as u32;
+                plan.spawners[plan.spawner_count] = SpawnerDef{position, rate, speed, activate};
+                plan.spawner_count += 1;
+            }
+        }
+
+        plan
+    }
+
+    /// Checks that the level can be finished by a player that times their
+    /// moves right, with the conveyors and lava the world scales to the
+    /// `scaling` percent difficulty.
+    pub fn solvable(&self, scaling: u32) -> bool {
+        let conveyors = &self.conveyors[..self.conveyor_count];
+        let lavas = &self.lavas[..self.lava_count];
+
+        // Conveyors must not overlap and the player has to be able to walk
+        // against them
+        for (i, c) in conveyors.iter().enumerate() {
+            if scale_conveyor_speed(c.speed, scaling).abs() >= PLAYER_SPEED || c.pos_start < 0 || c.pos_end > 999 {
+                return false;
+            }
+            for o in &conveyors[(i + 1)..] {
+                if c.pos_start < o.pos_end && o.pos_start < c.pos_end {
+                    return false;
+                }
+            }
+        }
+
+        for (i, l) in lavas.iter().enumerate() {
+            // Keep the start and the exit out of the lava
+            if l.pos_start < SAFE_ZONE || l.pos_end > EXIT_ZONE {
+                return false;
+            }
+            // Lavas need some room to stand in between
+            for o in &lavas[(i + 1)..] {
+                if l.pos_start < o.pos_end + PLAYER_SPEED && o.pos_start < l.pos_end + PLAYER_SPEED {
+                    return false;
+                }
+            }
+            // The player has to make it across while the lava is off, even
+            // when walking against a conveyor
+            let mut headwind = 0;
+            for c in conveyors {
+                if c.pos_start < l.pos_end && l.pos_start < c.pos_end && c.speed < 0 {
+                    headwind = headwind.max(-scale_conveyor_speed(c.speed, scaling));
+                }
+            }
+            let speed = PLAYER_SPEED - headwind;
+            let frames = ((l.pos_end - l.pos_start) + speed - 1) / speed;
+            if (frames as u32 * FRAME_TIME * 2) > scale_period(l.offtime, scaling) {
+                return false;
+            }
+        }
+
+        // Nothing may start right next to the player
+        for e in &self.enemies[..self.enemy_count] {
+            if e.position - (e.wobble / 2) < SAFE_ZONE {
+                return false;
+            }
+        }
+        for s in &self.spawners[..self.spawner_count] {
+            if s.position < SAFE_ZONE {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn build(&self, world: &mut World, time: u32) {
+        for c in &self.conveyors[..self.conveyor_count] {
+            world.spawn_conveyor(c.pos_start, c.pos_end, c.speed);
+        }
+        for l in &self.lavas[..self.lava_count] {
+            world.spawn_lava(time, l.pos_start, l.pos_end, l.ontime, l.offtime, l.offset, false);
+        }
+        for e in &self.enemies[..self.enemy_count] {
+            world.spawn_enemy(e.position, e.speed, e.wobble, EnemyKind::Basic);
+        }
+        for s in &self.spawners[..self.spawner_count] {
+            world.spawn_spawner(time, s.position, s.rate, s.speed, s.activate, EnemyKind::Basic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::{random16_get_seed, random16_set_seed};
+
+    #[test]
+    fn leaves_the_game_random_state_alone() {
+        random16_set_seed(1337);
+        let first = LevelPlan::generate(42, 10, 100);
+        assert_eq!(random16_get_seed(), 1337);
+        let second = LevelPlan::generate(42, 10, 100);
+        assert_eq!(first.enemy_count, second.enemy_count);
+        assert_eq!(first.lava_count, second.lava_count);
+    }
+
+    #[test]
+    fn lava_has_to_be_crossable_at_the_scaled_off_time() {
+        let mut plan = LevelPlan::empty();
+        // 300 wide takes 30 frames, twice that has to fit in the off time
+        plan.lavas[0] = LavaDef{pos_start: 400, pos_end: 700, ontime: 1000, offtime: 1000, offset: 0};
+        plan.lava_count = 1;
+        assert!(plan.solvable(100));
+        assert!(!plan.solvable(150));
+    }
+
+    #[test]
+    fn conveyors_have_to_be_walkable_at_the_scaled_speed() {
+        let mut plan = LevelPlan::empty();
+        plan.conveyors[0] = ConveyorDef{pos_start: 300, pos_end: 600, speed: -5};
+        plan.conveyor_count = 1;
+        plan.lavas[0] = LavaDef{pos_start: 400, pos_end: 500, ontime: 1000, offtime: 1000, offset: 0};
+        plan.lava_count = 1;
+        // Walking against the conveyor at 5, then at 2 once it speeds up
+        assert!(plan.solvable(100));
+        assert!(!plan.solvable(200));
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Golden frame tests for the state animations.
+//!
+//! Each test plays an animation frame by frame and compares the LED string
+//! against the reference in `golden/<name>.txt`, one run length encoded frame
+//! per line. Busy frames, like the rainbows, are kept as a `~` and a hash of
+//! the encoded frame to keep the files small. Every fourth frame is kept in
+//! full anyway (`FULL_FRAME_EVERY`), so the files still show what the
+//! animations look like. After an intended change to an animation, review it in the
+//! simulator and accept the new frames with:
+//!
+//!     TWANG_ACCEPT_GOLDEN=1 cargo test golden
+//!
+//! then check the diff of the golden files before committing them.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::string::String;
+use std::vec::Vec;
+use std::{env, format};
+
+use super::*;
+use super::rand::random16_set_seed;
+
+const FRAME_TIME: u32 = 16;
+const ACCEPT_VAR: &str = "TWANG_ACCEPT_GOLDEN";
+const MAX_LINE_LENGTH: usize = 200; // Longer frames are hashed
+const FULL_FRAME_EVERY: usize = 4;  // Frames kept in full no matter how long
+
+struct Capture {
+    twang: Twang,
+    time: u32,
+    frames: Vec<String>, // Run length encoded
+}
+
+impl Capture {
+    fn new() -> Self {
+        // The animations use the random generator, start from a known state
+        random16_set_seed(1337);
+        Self { twang: Twang::new(Config::default()), time: 1000, frames: Vec::new() }
+    }
+
+    fn level(level: u32) -> Self {
+        let mut capture = Self::new();
+        capture.twang.start_level(level, capture.time);
+        capture
+    }
+
+    fn cycle(&mut self, lr: i32, fire: bool) {
+        self.twang.cycle(lr, fire, self.time);
+        self.time += FRAME_TIME;
+    }
+
+    /// Runs without recording until the game reaches `status`.
+    fn skip_until(&mut self, lr: i32, fire: bool, status: Status) {
+        let end = self.time + 10000;
+        while self.twang.status() != status {
+            assert!(self.time < end, "expected {:?} got stuck in {:?}", status, self.twang.status());
+            self.cycle(lr, fire);
+        }
+    }
+
+    /// Records every frame while the game stays in `status`.
+    fn record_while(&mut self, status: Status) {
+        while self.twang.status() == status {
+            self.record();
+            self.cycle(0, false);
+            assert!(self.frames.len() < 1000, "{:?} never ends", status);
+        }
+    }
+
+    fn record(&mut self) {
+        let leds: Vec<[u8; 3]> = (0..self.twang.get_led_len()).map(|i| self.twang.get_led(i)).collect();
+        self.frames.push(encode(&leds));
+    }
+
+    fn check(&self, name: &str) {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "golden", &format!("{}.txt", name)].iter().collect();
+        let lines: Vec<String> = self.frames.iter().enumerate().map(|(n, frame)| golden_line(n, frame)).collect();
+        let mut actual = lines.join("\n");
+        actual.push('\n');
+
+        if env::var_os(ACCEPT_VAR).is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let golden = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!("no golden frames in {}, record them with {}=1", path.display(), ACCEPT_VAR)
+        });
+        let golden: Vec<&str> = golden.lines().collect();
+        for (n, (expected, got)) in golden.iter().zip(lines.iter()).enumerate() {
+            // Hashed frames don't tell much, show the whole frame
+            let frame = if got.starts_with('~') {format!(" = {}", self.frames[n])} else {String::new()};
+            assert!(expected == got, "{}: frame {} ({} ms in) differs\nexpected: {}\n     got: {}{}\n\
+                if this is intended accept it with {}=1", name, n, n as u32 * FRAME_TIME, expected, got, frame, ACCEPT_VAR);
+        }
+        assert!(golden.len() == lines.len(), "{}: expected {} frames, got {}, \
+            if this is intended accept it with {}=1", name, golden.len(), lines.len(), ACCEPT_VAR);
+    }
+}
+
+/// Encodes a frame as space separated `RRGGBB` colors, repeats as `N*RRGGBB`.
+fn encode(leds: &[[u8; 3]]) -> String {
+    let mut line = String::new();
+    let mut i = 0;
+    while i < leds.len() {
+        let run = leds[i..].iter().take_while(|led| **led == leds[i]).count();
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        if run > 1 {
+            write!(line, "{}*", run).unwrap();
+        }
+        write!(line, "{:02x}{:02x}{:02x}", leds[i][0], leds[i][1], leds[i][2]).unwrap();
+        i += run;
+    }
+    line
+}
+
+fn golden_line(n: usize, frame: &str) -> String {
+    if frame.len() <= MAX_LINE_LENGTH || n.is_multiple_of(FULL_FRAME_EVERY) {
+        return String::from(frame);
+    }
+    // FNV-1a
+    let hash = frame.bytes().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("~{:016x}", hash)
+}
+
+#[test]
+fn encodes_runs() {
+    assert_eq!(encode(&[[0, 0, 0], [0, 0, 0], [255, 16, 1], [0, 0, 0]]), "2*000000 ff1001 000000");
+}
+
+#[test]
+fn golden_startup() {
+    let mut capture = Capture::new();
+    capture.cycle(0, true);
+    capture.record_while(Status::Starting);
+    capture.check("startup");
+}
+
+#[test]
+fn golden_death() {
+    let mut capture = Capture::level(1);
+    capture.skip_until(10, false, Status::Death{level: 1});
+    capture.record_while(Status::Death{level: 1});
+    capture.check("death");
+}
+
+#[test]
+fn golden_game_over() {
+    let mut capture = Capture::level(1);
+    capture.twang.set_lives(1);
+    capture.skip_until(10, false, Status::Death{level: 1});
+    capture.skip_until(0, false, Status::GameOver);
+    capture.record_while(Status::GameOver);
+    capture.check("game_over");
+}
+
+#[test]
+fn golden_win() {
+    let mut capture = Capture::level(0);
+    // Walk up to the enemy and kill it
+    while capture.twang.player_position() < 470 {
+        capture.cycle(10, false);
+    }
+    capture.cycle(0, true);
+    capture.skip_until(10, false, Status::Win{level: 0});
+    capture.record_while(Status::Win{level: 0});
+    capture.check("win");
+}
+
+#[test]
+fn golden_boss_rainbow() {
+    let mut capture = Capture::level(LEVEL_COUNT - 1);
+    for _ in 0..BossKind::Dungeon.phases().len() {
+        capture.twang.world.hit_boss(capture.time);
+    }
+    capture.cycle(0, false);
+    capture.record_while(Status::BossKilled);
+    capture.check("boss_rainbow");
+}
@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Game input from the controls, shared by the firmware and the simulator.
+
+pub const LR_MAX: i32 = 10; // Player speed with the stick pushed all the way
+
+/// The controls for one frame, as `Twang::cycle` takes them.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Input {
+    pub lr: i32,    // Player speed, -LR_MAX (left) to LR_MAX (right)
+    pub fire: bool,
+}
+
+impl Input {
+    /// A digital joystick, it fires pushed up or down.
+    pub fn from_buttons(left: bool, right: bool, up: bool, down: bool) -> Self {
+        Self {
+            lr: (right as i32 - left as i32) * LR_MAX,
+            fire: up || down,
+        }
+    }
+
+    /// An analog stick axis going from -max to max, ignoring the `deadzone`
+    /// around the center.
+    pub fn from_axis(value: i32, max: i32, deadzone: i32, fire: bool) -> Self {
+        let lr = if value.abs() <= deadzone {
+            0
+        } else {
+            (value * LR_MAX / max).clamp(-LR_MAX, LR_MAX)
+        };
+        Self { lr, fire }
+    }
+
+    /// Combines two controls steering the same player, opposite directions
+    /// cancel out.
+    pub fn merge(self, other: Input) -> Self {
+        Self {
+            lr: (self.lr + other.lr).clamp(-LR_MAX, LR_MAX),
+            fire: self.fire || other.fire,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buttons() {
+        assert_eq!(Input::from_buttons(true, false, false, false), Input{lr: -LR_MAX, fire: false});
+        assert_eq!(Input::from_buttons(true, true, false, true), Input{lr: 0, fire: true});
+    }
+
+    #[test]
+    fn axis_with_deadzone() {
+        assert_eq!(Input::from_axis(1000, 32767, 4000, false).lr, 0);
+        assert_eq!(Input::from_axis(-16384, 32767, 4000, false).lr, -5);
+        assert_eq!(Input::from_axis(32767, 32767, 4000, true), Input{lr: LR_MAX, fire: true});
+        assert_eq!(Input::from_axis(-32768, 32767, 4000, false).lr, -LR_MAX);
+    }
+
+    #[test]
+    fn merging_cancels_and_clamps() {
+        let left = Input::from_buttons(true, false, false, false);
+        let right = Input::from_buttons(false, true, true, false);
+        assert_eq!(left.merge(right), Input{lr: 0, fire: true});
+        assert_eq!(right.merge(right).lr, LR_MAX);
+    }
+}
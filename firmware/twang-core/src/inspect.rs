@@ -0,0 +1,102 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Read only views of the world for debugging front ends.
+//!
+//! All positions are virtual world coordinates, 0 to `LED_STRING_VLENGTH - 1`.
+
+use core::fmt;
+
+pub use crate::enemy::EnemyKind;
+
+/// What occupies a hitbox.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HitboxKind {
+    Player,
+    Attack,     // Reach of the running player attack
+    Enemy,
+    Lava,
+    LavaFlow,
+    Conveyor,
+    Portal,
+    Boss,
+    BossLava,   // Lava breath on either side of the boss
+}
+
+/// A stretch of the world, from `start` to `end` inclusive, where something
+/// collides with the player.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hitbox {
+    pub kind: HitboxKind,
+    pub start: i32,
+    pub end: i32,
+    pub deadly: bool,   // Touching it kills the player right now
+}
+
+impl Hitbox {
+    pub fn new(kind: HitboxKind, start: i32, end: i32, deadly: bool) -> Self {
+        Self { kind, start, end, deadly }
+    }
+}
+
+/// The state of one live entity, see `Twang::inspect`. Times are in ms from now.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Entity {
+    Player{position: i32, lives: u8, attacking: bool, attack_start: i32, attack_end: i32, attack_left: u32, cooldown_left: u32},
+    Enemy{position: i32, speed: i32, kind: EnemyKind, player_side: i32},
+    Spawner{position: i32, kind: EnemyKind, next_spawn: u32},
+    Lava{start: i32, end: i32, on: bool, switch_in: u32},
+    LavaFlow{start: i32, end: i32, hot: bool},
+    Conveyor{start: i32, end: i32, speed: i32},
+    Portal{a: i32, b: i32},
+    Boss{position: i32, start: i32, stop: i32, hits_left: u32, lava_on: bool},
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Entity::Player{position, lives, attacking, attack_start, attack_end, attack_left, cooldown_left} => {
+                write!(f, "player {} lives {} attack {}..{}", position, lives, attack_start, attack_end)?;
+                if attacking {
+                    write!(f, " for {}ms", attack_left)?;
+                }
+                write!(f, " cooldown {}ms", cooldown_left)
+            }
+            Entity::Enemy{position, speed, kind, player_side} =>
+                write!(f, "enemy {} {:?} speed {} side {}", position, kind, speed, player_side),
+            Entity::Spawner{position, kind, next_spawn} =>
+                write!(f, "spawner {} {:?} next in {}ms", position, kind, next_spawn),
+            Entity::Lava{start, end, on, switch_in} =>
+                write!(f, "lava {}..{} {} for {}ms", start, end, if on {"on"} else {"off"}, switch_in),
+            Entity::LavaFlow{start, end, hot} =>
+                write!(f, "lava flow {}..{}{}", start, end, if hot {""} else {" out"}),
+            Entity::Conveyor{start, end, speed} =>
+                write!(f, "conveyor {}..{} speed {}", start, end, speed),
+            Entity::Portal{a, b} =>
+                write!(f, "portal {} <> {}", a, b),
+            Entity::Boss{position, start, stop, hits_left, lava_on} =>
+                write!(f, "boss {} at {}..{} hits {} lava {}", position, start, stop, hits_left, if lava_on {"on"} else {"off"}),
+        }
+    }
+}
@@ -23,10 +23,12 @@
  */
 
 use super::{led_string::LEDString, rand::random8lim};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
 
 const LAVA_OFF_BRIGHTNESS: u8 = 15;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct Lava {
     pub pos_start: i32,
     pub pos_end: i32,
@@ -90,10 +92,21 @@ impl Lava {
         }
     }
 
+    pub fn inspect(&self, time: u32) -> Entity {
+        let period = if self.state {self.ontime} else {self.offtime};
+        Entity::Lava {
+            start: self.pos_start,
+            end: self.pos_end,
+            on: self.state,
+            switch_in: (self.laston + period + 1).saturating_sub(time),
+        }
+    }
+
     pub fn reset(&mut self) {
         self.alive = false;
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn(&mut self, time: u32, pos_start: i32, pos_end: i32, ontime: u32, offtime: u32, offset: u32, state: bool) {
         self.pos_start = pos_start;
         self.pos_end = pos_end;
@@ -104,4 +117,64 @@ impl Lava {
         self.state = state;
         self.alive = true;
     }
-}
\ No newline at end of file
+}
+
+impl Save for Lava {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.pos_start);
+        w.i32(self.pos_end);
+        w.u32(self.ontime);
+        w.u32(self.offtime);
+        w.u32(self.offset);
+        w.u32(self.laston);
+        w.bool(self.state);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.pos_start = r.i32()?;
+        self.pos_end = r.i32()?;
+        self.ontime = r.u32()?;
+        self.offtime = r.u32()?;
+        self.offset = r.u32()?;
+        self.laston = r.u32()?;
+        self.state = r.bool()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_on_and_off() {
+        let mut lava = Lava::new();
+        lava.spawn(1000, 400, 500, 300, 200, 0, false);
+        lava.tick(1200);
+        assert!(!lava.state);
+        lava.tick(1201);
+        assert!(lava.state);
+        lava.tick(1501);
+        assert!(lava.state);
+        lava.tick(1502);
+        assert!(!lava.state);
+    }
+
+    #[test]
+    fn counts_down_to_the_switch() {
+        let mut lava = Lava::new();
+        lava.spawn(1000, 400, 500, 300, 200, 0, false);
+        assert_eq!(lava.inspect(1100), Entity::Lava{start: 400, end: 500, on: false, switch_in: 101});
+        lava.tick(1201);
+        assert_eq!(lava.inspect(1201), Entity::Lava{start: 400, end: 500, on: true, switch_in: 301});
+    }
+}
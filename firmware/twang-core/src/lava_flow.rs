@@ -0,0 +1,202 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::{led_string::LEDString, player::Player, rand::random8lim};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+
+const LAVA_FLOW_OUT_DUR: u32 = 1500; // Time an attack puts the flow out for
+
+/// Lava that flows along the track bouncing between two bounds.
+#[derive(Clone, Copy, Debug)]
+pub struct LavaFlow {
+    pub pos_start: i32,
+    pub pos_end: i32,
+    width: i32,
+    speed: i32,
+    bound_min: i32,
+    bound_max: i32,
+    pub hot: bool,      // Put out flows keep moving but are harmless
+    out_millis: u32,    // Time the flow was put out
+    pub alive: bool,
+}
+
+impl LavaFlow {
+    pub fn new() -> Self {
+        Self {
+            pos_start: 0,
+            pos_end: 0,
+            width: 0,
+            speed: 0,
+            bound_min: 0,
+            bound_max: 0,
+            hot: true,
+            out_millis: 0,
+            alive: false,
+        }
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString) {
+        if !self.alive {
+            return;
+        }
+        let start = led_string.vtor(self.pos_start);
+        let end = led_string.vtor(self.pos_end);
+        // Hot leading edge, cooling tail
+        let (head, tail) = if self.speed >= 0 {(end, start)} else {(start, end)};
+        if !self.hot {
+            for i in start..(end + 1) {
+                led_string[i].set_rgb([20, 2, 0]);
+            }
+            return;
+        }
+        for i in start..(end + 1) {
+            let flicker = random8lim(60);
+            if i == head {
+                led_string[i].set_rgb([255, 120 + flicker, 0]);
+            } else if i == tail {
+                led_string[i].set_rgb([80 + flicker, 10, 0]);
+            } else {
+                led_string[i].set_rgb([180 + flicker, flicker, 0]);
+            }
+        }
+    }
+
+    pub fn tick(&mut self, time: u32) {
+        if !self.alive {
+            return;
+        }
+        if !self.hot && time > self.out_millis + LAVA_FLOW_OUT_DUR {
+            self.hot = true;
+        }
+        self.pos_start += self.speed;
+        if self.pos_start <= self.bound_min {
+            self.pos_start = self.bound_min;
+            self.speed = self.speed.abs();
+        } else if self.pos_start + self.width >= self.bound_max {
+            self.pos_start = self.bound_max - self.width;
+            self.speed = -self.speed.abs();
+        }
+        self.pos_end = self.pos_start + self.width;
+    }
+
+    /// The player attack puts the flow out for a while
+    pub fn collide_player(&mut self, player: &Player, time: u32) {
+        if !self.alive || !self.hot || !player.attacking {
+            return;
+        }
+        let amin = player.position - (player.attack_cur_width / 2);
+        let amax = player.position + (player.attack_cur_width / 2);
+        if (amin <= self.pos_end) && (amax >= self.pos_start) {
+            self.hot = false;
+            self.out_millis = time;
+        }
+    }
+
+    pub fn inspect(&self) -> Entity {
+        Entity::LavaFlow{start: self.pos_start, end: self.pos_end, hot: self.hot}
+    }
+
+    pub fn reset(&mut self) {
+        self.alive = false;
+    }
+
+    pub fn spawn(&mut self, position: i32, width: i32, speed: i32, bound_min: i32, bound_max: i32) {
+        self.pos_start = position;
+        self.pos_end = position + width;
+        self.width = width;
+        self.speed = speed;
+        self.bound_min = bound_min;
+        self.bound_max = bound_max;
+        self.hot = true;
+        self.alive = true;
+    }
+}
+
+impl Save for LavaFlow {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.pos_start);
+        w.i32(self.pos_end);
+        w.i32(self.width);
+        w.i32(self.speed);
+        w.i32(self.bound_min);
+        w.i32(self.bound_max);
+        w.bool(self.hot);
+        w.u32(self.out_millis);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.pos_start = r.i32()?;
+        self.pos_end = r.i32()?;
+        self.width = r.i32()?;
+        self.speed = r.i32()?;
+        self.bound_min = r.i32()?;
+        self.bound_max = r.i32()?;
+        self.hot = r.bool()?;
+        self.out_millis = r.u32()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounces_between_bounds() {
+        let mut flow = LavaFlow::new();
+        flow.spawn(570, 20, 5, 500, 600);
+        flow.tick(0);
+        flow.tick(0);
+        assert_eq!(flow.pos_end, 600);
+        flow.tick(0);
+        assert_eq!(flow.pos_start, 575);
+    }
+
+    #[test]
+    fn attack_puts_it_out_for_a_while() {
+        let mut flow = LavaFlow::new();
+        flow.spawn(540, 20, 0, 500, 600);
+        let mut player = Player::new(1);
+        player.spawn(510);
+        flow.collide_player(&player, 1000);
+        assert!(flow.hot);
+        player.attack(true, 1000);
+        flow.collide_player(&player, 1000);
+        assert!(!flow.hot);
+        flow.tick(1000 + LAVA_FLOW_OUT_DUR);
+        assert!(!flow.hot);
+        flow.tick(1000 + LAVA_FLOW_OUT_DUR + 1);
+        assert!(flow.hot);
+    }
+}
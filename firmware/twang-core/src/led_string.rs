@@ -24,16 +24,19 @@
 
 use core::ops::{Index, IndexMut, AddAssign};
 
-use crate::LED_STRING_LENGTH;
-use crate::twang::utils::range_map;
+use crate::utils::range_map;
 // use std::iter::IntoIterator;
-const LED_STRING_VLENGTH: usize = 1000;
+pub const LED_STRING_VLENGTH: usize = 1000; // Length of the virtual world the entities live in
+/// Longest strip the game can drive, one LED per world position. Hardware
+/// with less LED memory has to check its own limit.
+pub const LED_STRING_MAX_LENGTH: usize = LED_STRING_VLENGTH;
 
 /*****************************************************************************
  * LED
  *****************************************************************************/
 
 #[derive(Clone, Debug, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct LED {
     pub r: u8,
     pub g: u8,
@@ -78,23 +81,35 @@ impl AddAssign for LED {
  * LEDString
  *****************************************************************************/
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct LEDString {
-	leds: [LED; LED_STRING_LENGTH],
+	leds: [LED; LED_STRING_MAX_LENGTH],
+    len: usize,
     null: LED,
 }
 
 impl LEDString {
 
-	pub fn new() -> LEDString {
+	pub fn new(len: usize) -> LEDString {
+        check_len(len);
 		LEDString {
-			leds: [LED::new([0; 3]); LED_STRING_LENGTH],
+			leds: [LED::new([0; 3]); LED_STRING_MAX_LENGTH],
+            len,
             null: LED::new([0; 3])
 		}
 	}
 
     pub fn len(&self) -> i32 {
-        LED_STRING_LENGTH as i32
+        self.len as i32
+    }
+
+    /// Changes the number of LEDs in use, panics like `new` when out of range.
+    pub fn set_len(&mut self, len: usize) {
+        check_len(len);
+        self.len = len;
+        for led in &mut self.leds {
+            led.set_rgb([0; 3]);
+        }
     }
 
     pub fn vlen(&self) -> i32 {
@@ -104,23 +119,23 @@ impl LEDString {
     /// Converts the virtual world coordinate to the physical coordinate.
     pub fn vtor(& self, n: i32) -> i32 {
         let n = if n >= LED_STRING_VLENGTH as i32 {LED_STRING_VLENGTH as i32 - 1} else {n};
-        range_map(n, 0, LED_STRING_VLENGTH as i32 - 1, 0, LED_STRING_LENGTH as i32 - 1)
+        range_map(n, 0, LED_STRING_VLENGTH as i32 - 1, 0, self.len as i32 - 1)
     }
 
     pub fn clear(&mut self){
-        for led in &mut self.leds {
+        for led in &mut self.leds[..self.len] {
             led.set_rgb([0; 3]);
         }
     }
 
     pub fn nscale8(&mut self, scale: u8) {
-        for led in &mut self.leds {
+        for led in &mut self.leds[..self.len] {
             led.nscale8(scale);
         }
     }
 
     pub fn get_raw(&mut self, i: i32) -> &LED {
-        if i < 0 || i >= LED_STRING_LENGTH as i32 {
+        if i < 0 || i >= self.len as i32 {
             &self.null
         } else {
             &self.leds[i as usize]
@@ -132,7 +147,7 @@ impl Index<i32> for LEDString {
     type Output = LED;
 
     fn index(&self, i: i32) -> &Self::Output {
-        if i < 0 || i >= LED_STRING_LENGTH as i32 {
+        if i < 0 || i >= self.len as i32 {
             &self.null
         } else {
             &self.leds[i as usize]
@@ -142,7 +157,7 @@ impl Index<i32> for LEDString {
 
 impl IndexMut<i32> for LEDString {
     fn index_mut(&mut self, i: i32) -> &mut Self::Output {
-        if i < 0 || i >= LED_STRING_LENGTH as i32 {
+        if i < 0 || i >= self.len as i32 {
             &mut self.null
         } else {
             &mut self.leds[i as usize]
@@ -150,6 +165,11 @@ impl IndexMut<i32> for LEDString {
     }
 }
 
+fn check_len(len: usize) {
+    assert!((1..=LED_STRING_MAX_LENGTH).contains(&len),
+        "LED string length {} out of 1..={}", len, LED_STRING_MAX_LENGTH);
+}
+
 /*****************************************************************************
  * Value operator functions
  *****************************************************************************/
@@ -166,7 +186,7 @@ impl IndexMut<i32> for LEDString {
 // If both inputs are non-zero, the output is guaranteed to be non-zero. This makes for better 'video'/LED dimming, at
 // the cost of several additional cycles.
 pub fn scale8_video(val: u8, scaler: u8) -> u8 {
-    let mut ret = (val as u16 * scaler as u16) >> 8 as u8;
+    let mut ret = (val as u16 * scaler as u16) >> 8;
     if val > 0 && scaler > 0 {
         ret += 1;
     }
@@ -280,7 +300,7 @@ pub fn scale8_video(val: u8, scaler: u8) -> u8 {
     // This is one of the good places to scale the green down,
     // although the client can scale green down as well.
     if g2 {
-        g = g >> 1;
+        g >>= 1;
     }
     if g_scale > 0 {
         g = scale8_video(g, g_scale);
@@ -337,4 +357,50 @@ pub fn scale8_video(val: u8, scaler: u8) -> u8 {
     }
 
     [r, g, b]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_rainbow_primaries() {
+        assert_eq!(hsv_rainbow(0, 255, 255), [255, 0, 0]);
+        assert_eq!(hsv_rainbow(32, 255, 255), [171, 85, 0]);
+        assert_eq!(hsv_rainbow(96, 255, 255), [0, 255, 0]);
+        assert_eq!(hsv_rainbow(160, 255, 255), [0, 0, 255]);
+    }
+
+    #[test]
+    fn hsv_rainbow_saturation_and_value() {
+        for h in 0..=255u8 {
+            assert_eq!(hsv_rainbow(h, 0, 255), [255, 255, 255], "h = {}", h);
+            assert_eq!(hsv_rainbow(h, 255, 0), [0, 0, 0], "h = {}", h);
+        }
+    }
+
+    #[test]
+    fn hsv_rainbow_full_range() {
+        // Should never overflow, and dimmer never gets brighter
+        for h in 0..=255u8 {
+            for s in (0..=255u8).step_by(5) {
+                let mut prev = hsv_rainbow(h, s, 255);
+                for v in (0..=250u8).rev().step_by(5) {
+                    let c = hsv_rainbow(h, s, v);
+                    for i in 0..3 {
+                        assert!(c[i] <= prev[i], "h = {} s = {} v = {}", h, s, v);
+                    }
+                    prev = c;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn vtor_maps_world_to_strip() {
+        let led_string = LEDString::new(144);
+        assert_eq!(led_string.vtor(0), 0);
+        assert_eq!(led_string.vtor(led_string.vlen() - 1), led_string.len() - 1);
+        assert_eq!(led_string.vtor(500), (led_string.len() - 1) / 2);
+    }
+}
@@ -0,0 +1,914 @@
+/*
+ * Copyright (c) 2020-2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! The TWANG game engine shared by the firmware and the host tools.
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+pub mod frame;
+pub mod input;
+pub mod inspect;
+pub mod log;
+pub mod profile;
+pub mod snapshot;
+mod led_string;
+mod utils;
+mod attract;
+mod world;
+mod player;
+mod enemy;
+mod spawner;
+mod lava;
+mod lava_flow;
+mod conveyor;
+mod portal;
+mod rand;
+mod particle;
+mod boss;
+mod generator;
+
+use world::World;
+use enemy::EnemyKind;
+use boss::BossKind;
+use generator::LevelPlan;
+pub use led_string::{LED_STRING_MAX_LENGTH, LED_STRING_VLENGTH};
+// The random generator is global, saving a game state has to include its seed
+pub use rand::{random16_get_seed, random16_set_seed};
+use led_string::LEDString;
+use inspect::{Entity, Hitbox};
+use log::{Event, Level, Log, Logger};
+use profile::{Profiler, Section};
+use snapshot::{Reader, Save, SnapshotError, Writer};
+use core::fmt;
+
+use self::{rand::{random16lim, random8, random8lim}, utils::sinu8};
+
+use utils::{range_map, constrain};
+
+const GAME_TIMEOUT: u32 = 60000;
+const STARTUP_WIPEUP_DUR: u32 = 200;
+const STARTUP_SPARKLE_DUR: u32 = 1300;
+const STARTUP_FADE_DUR: u32 = 1500;
+const DEATH_EXPLOSION_DUR: u32 = 200;
+const DEATH_EXPLOSION_WIDTH: i32 = 10;
+const LIVES_DISPLAY_DUR: u32 = 1000;
+const PLAYER_DEFAULT_LIVES: u8 = 3;
+const GAMEOVER_SPREAD_DUR: u32 = 1000;
+const GAMEOVER_FADE_DUR: u32 = 1500;
+const WIN_FILL_DUR: u32 = 500;
+const WIN_CLEAR_DUR: u32 = 1000;
+const WIN_OFF_DUR: u32 = 1200;
+const BOSSKILLED_RAINBOW_DUR: u32 = 7500;
+const BOSSKILLED_WIPE_DUR: u32 = 1000;
+const LOOP_DIFFICULTY_STEP: u32 = 25; // Percent the game gets harder every loop
+pub const LEVEL_COUNT: u32 = 25; // Number of handcrafted levels, the last one is the final boss
+const GENERATOR_SEED: u16 = 0x7A36;
+
+#[derive(Clone, Copy)]
+enum StartStage {
+    Wipeup,
+    Sparkle,
+    Fade,
+}
+
+#[derive(Clone, Copy)]
+enum DeathStage {
+    Explosion,
+    Particles
+}
+
+#[derive(Clone, Copy)]
+enum GameOverStage {
+    Spread,
+    Fade
+}
+
+#[derive(Clone, Copy)]
+enum WinStage {
+    Fill,
+    Clear,
+    Off,
+}
+
+#[derive(Clone, Copy)]
+enum BossKilledStage {
+    Rainbow{hue: u8},
+    Wipe,
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Screensaver,
+    Starting{stage: StartStage, start_time: u32},
+    Playing{level: u32, timeout: u32},
+    Death{level: u32, stage: DeathStage, start_time: u32},
+    Lives{level: u32, start_time: u32},
+    GameOver{stage: GameOverStage, start_time: u32},
+    Win{level: u32, stage: WinStage, start_time: u32},
+    BossKilled{stage: BossKilledStage, start_time: u32},
+}
+
+impl Save for State {
+    fn save(&self, w: &mut Writer) {
+        match *self {
+            State::Screensaver => w.u8(0),
+            State::Starting{stage, start_time} => {
+                w.u8(1);
+                w.u8(stage as u8);
+                w.u32(start_time);
+            }
+            State::Playing{level, timeout} => {
+                w.u8(2);
+                w.u32(level);
+                w.u32(timeout);
+            }
+            State::Death{level, stage, start_time} => {
+                w.u8(3);
+                w.u32(level);
+                w.u8(stage as u8);
+                w.u32(start_time);
+            }
+            State::Lives{level, start_time} => {
+                w.u8(4);
+                w.u32(level);
+                w.u32(start_time);
+            }
+            State::GameOver{stage, start_time} => {
+                w.u8(5);
+                w.u8(stage as u8);
+                w.u32(start_time);
+            }
+            State::Win{level, stage, start_time} => {
+                w.u8(6);
+                w.u32(level);
+                w.u8(stage as u8);
+                w.u32(start_time);
+            }
+            State::BossKilled{stage, start_time} => {
+                w.u8(7);
+                match stage {
+                    BossKilledStage::Rainbow{hue} => {
+                        w.u8(0);
+                        w.u8(hue);
+                    }
+                    BossKilledStage::Wipe => w.u8(1),
+                }
+                w.u32(start_time);
+            }
+        }
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        use SnapshotError::Invalid;
+        *self = match r.u8()? {
+            0 => State::Screensaver,
+            1 => {
+                let stage = match r.u8()? {
+                    0 => StartStage::Wipeup,
+                    1 => StartStage::Sparkle,
+                    2 => StartStage::Fade,
+                    _ => return Err(Invalid),
+                };
+                State::Starting{stage, start_time: r.u32()?}
+            }
+            2 => State::Playing{level: r.u32()?, timeout: r.u32()?},
+            3 => {
+                let level = r.u32()?;
+                let stage = match r.u8()? {
+                    0 => DeathStage::Explosion,
+                    1 => DeathStage::Particles,
+                    _ => return Err(Invalid),
+                };
+                State::Death{level, stage, start_time: r.u32()?}
+            }
+            4 => State::Lives{level: r.u32()?, start_time: r.u32()?},
+            5 => {
+                let stage = match r.u8()? {
+                    0 => GameOverStage::Spread,
+                    1 => GameOverStage::Fade,
+                    _ => return Err(Invalid),
+                };
+                State::GameOver{stage, start_time: r.u32()?}
+            }
+            6 => {
+                let level = r.u32()?;
+                let stage = match r.u8()? {
+                    0 => WinStage::Fill,
+                    1 => WinStage::Clear,
+                    2 => WinStage::Off,
+                    _ => return Err(Invalid),
+                };
+                State::Win{level, stage, start_time: r.u32()?}
+            }
+            7 => {
+                let stage = match r.u8()? {
+                    0 => BossKilledStage::Rainbow{hue: r.u8()?},
+                    1 => BossKilledStage::Wipe,
+                    _ => return Err(Invalid),
+                };
+                State::BossKilled{stage, start_time: r.u32()?}
+            }
+            _ => return Err(Invalid),
+        };
+        Ok(())
+    }
+}
+
+/// Summary of what the game is doing, for tools driving the game.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Screensaver,
+    Starting,
+    Playing{level: u32},
+    Death{level: u32},
+    Lives{level: u32},
+    GameOver,
+    Win{level: u32},
+    BossKilled,
+}
+
+/// Game setup, handed to `Twang::new`.
+#[derive(Clone, Copy)]
+pub struct Config {
+    pub led_string_length: usize, // Number of LEDs on the strip, up to LED_STRING_MAX_LENGTH
+    pub endless: bool,            // Generate all levels instead of playing the handcrafted ones
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            led_string_length: 144,
+            endless: false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Twang {
+    led_string: LEDString,
+    screensaver: attract::Attract,
+    state: State,
+    world: World,
+    game_loop: u32, // Number of times the boss was killed in this game
+    endless: bool,  // Generate all levels instead of playing the handcrafted ones
+    log: Log,
+    profiler: Profiler,
+}
+
+impl Twang {
+    pub fn new(config: Config) -> Twang {
+        Twang {
+            led_string: LEDString::new(config.led_string_length),
+            screensaver: attract::Attract::new(),
+            state: State::Screensaver,
+            world: World::new(),
+            game_loop: 0,
+            endless: config.endless,
+            log: Log::new(),
+            profiler: Profiler::new(),
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        match self.state {
+            State::Screensaver => Status::Screensaver,
+            State::Starting{..} => Status::Starting,
+            State::Playing{level, ..} => Status::Playing{level},
+            State::Death{level, ..} => Status::Death{level},
+            State::Lives{level, ..} => Status::Lives{level},
+            State::GameOver{..} => Status::GameOver,
+            State::Win{level, ..} => Status::Win{level},
+            State::BossKilled{..} => Status::BossKilled,
+        }
+    }
+
+    /// Jumps straight into a level, skipping the start animation.
+    pub fn start_level(&mut self, level: u32, time: u32) {
+        self.world.player_set_lives(PLAYER_DEFAULT_LIVES);
+        self.build_level(level, time);
+        self.state = State::Playing{level, timeout: time};
+    }
+
+    pub fn set_lives(&mut self, lives: u8) {
+        self.world.player_set_lives(lives);
+    }
+
+    /// In god mode nothing can kill the player.
+    pub fn set_god_mode(&mut self, god: bool) {
+        self.world.player_set_god(god);
+    }
+
+    pub fn god_mode(&self) -> bool {
+        self.world.player_god()
+    }
+
+    /// Writes the game state and all live world entities.
+    pub fn dump(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(out, "{:?} loop {} lives {}", self.status(), self.game_loop, self.world.player_lives())?;
+        self.world.dump(out)
+    }
+
+    pub fn player_position(&self) -> i32 {
+        self.world.player_position()
+    }
+
+    pub fn enemies_alive(&self) -> u32 {
+        self.world.enemies_alive()
+    }
+
+    pub fn boss_hits_left(&self) -> u32 {
+        self.world.boss_hits_left()
+    }
+
+    /// Calls `f` with the hitbox of every live entity in the world.
+    pub fn hitboxes(&self, f: &mut dyn FnMut(Hitbox)) {
+        self.world.hitboxes(f);
+    }
+
+    /// Calls `f` with the state of every live world entity at `time`, the
+    /// time of the last `cycle`.
+    pub fn inspect(&self, time: u32, f: &mut dyn FnMut(Entity)) {
+        self.world.inspect(time, f);
+    }
+
+    /// LED showing the virtual world `position`.
+    pub fn world_to_led(&self, position: i32) -> usize {
+        self.led_string.vtor(position) as usize
+    }
+
+    /// Only events at this level or more severe are logged.
+    pub fn set_log_level(&mut self, level: Level) {
+        self.log.set_level(level);
+    }
+
+    /// Hands the events logged since the last call to the backend.
+    pub fn drain_log(&mut self, logger: &mut dyn Logger) {
+        self.log.drain(logger);
+    }
+
+    pub fn cycle(&mut self, lr_input: i32, fire_input: bool, time: u32) {
+        let status = self.status();
+        let mode = self.screensaver.mode;
+
+        self.state = match self.state {
+            State::Screensaver => {
+                self.screensaver.tick(&mut self.led_string, time);
+                if lr_input != 0 || fire_input {
+                    State::Starting{stage: StartStage::Wipeup, start_time: time}
+                } else {
+                    State::Screensaver
+                }
+            },
+            State::Starting {stage, start_time} => {
+                self.led_string.clear();
+                match stage {
+                    StartStage::Wipeup => {
+                        let n = range_map(time - start_time, 0, STARTUP_WIPEUP_DUR, 0, self.led_string.len() as u32) as i32;
+                        for i in 0..n {
+                            self.led_string[i].set_rgb([0, 255, 0])
+                        }
+                        if time < (start_time + STARTUP_WIPEUP_DUR) {
+                            State::Starting{stage, start_time}
+                        } else {
+                            State::Starting{stage: StartStage::Sparkle, start_time: time}
+                        }
+                    },
+                    StartStage::Sparkle => {
+                        // we need rand to sparkle
+                        for i in 0..self.led_string.len() {
+                            if random8lim(30) < 28 {
+                                self.led_string[i].set_rgb([0, 255, 0]);
+                            } else {
+                                let flicker = random8lim(250);
+                                self.led_string[i].set_rgb([flicker, 150, flicker]);
+                            }
+                        }
+                        if time < (start_time + STARTUP_SPARKLE_DUR) {
+                            State::Starting{stage, start_time}
+                        } else {
+                            State::Starting{stage: StartStage::Fade, start_time: time}
+                        }
+                    },
+                    StartStage::Fade => {
+                        let n = range_map(time - start_time, 0, STARTUP_FADE_DUR, 0, self.led_string.len() as u32) as i32;
+                        let brightness = range_map(time - start_time, 0, STARTUP_FADE_DUR, 255, 0) as u8;
+                        //println!("st{} t{} td{} n{} b{}", start_time, time, time-start_time, n, brightness);
+                        for i in n..self.led_string.len() {
+                            self.led_string[i].set_rgb([0, brightness, 0]);
+                        }
+                        if time < (start_time + STARTUP_FADE_DUR) {
+                            State::Starting{stage, start_time}
+                        } else {
+                            self.world.player_set_lives(PLAYER_DEFAULT_LIVES);
+                            self.set_game_loop(0);
+                            self.build_level(0, time);
+                            State::Playing{level: 0, timeout: time}
+                        }
+                    }
+                }
+            },
+            State::Playing{level, timeout} => {
+                self.world.player_attack(fire_input, time);
+                self.world.player_set_speed(lr_input);
+                self.profiler.begin(Section::Tick);
+                self.world.tick(&self.led_string, time);
+                self.profiler.end(Section::Tick);
+                self.profiler.begin(Section::Collide);
+                self.world.collide(time);
+                self.profiler.end(Section::Collide);
+                self.profiler.begin(Section::Draw);
+                self.led_string.clear();
+                self.world.draw(&mut self.led_string, time);
+                self.profiler.end(Section::Draw);
+
+                // Decide state transition
+                if (time - timeout) > GAME_TIMEOUT {
+                    State::Screensaver
+                } else if !self.world.player_alive() {
+                    let pos = self.world.player_position();
+                    self.world.spawn_particles(pos);
+                    State::Death{level, stage: DeathStage::Explosion, start_time: time}
+                } else if self.world.boss_killed() && level == LEVEL_COUNT - 1 {
+                    State::BossKilled{stage: BossKilledStage::Rainbow{hue: 0}, start_time: time}
+                } else if self.world.exit_n() || self.world.boss_killed() {
+                    // Bosses before the last one just open the way on
+                    State::Win{level, stage: WinStage::Fill, start_time: time}
+                } else if lr_input == 0 && !fire_input {
+                    State::Playing{level, timeout}
+                } else {
+                    State::Playing{level, timeout: time}
+                }
+            },
+            State::Death {level, stage, start_time} => {
+                self.led_string.clear();
+                // Death Animation
+                match stage {
+                    DeathStage::Explosion => {
+                        self.world.cycle_particles(&mut self.led_string, false, 0);
+                        let brightness = range_map(time - start_time, 0, DEATH_EXPLOSION_DUR, 255, 50) as u8;
+                        let pos = self.led_string.vtor(self.world.player_position());
+                        let start = constrain(range_map((time - start_time) as i32, 0, DEATH_EXPLOSION_DUR as i32, pos, pos - DEATH_EXPLOSION_WIDTH), 0, self.led_string.len() - 1);
+                        let stop = constrain(range_map((time - start_time) as i32, 0, DEATH_EXPLOSION_DUR as i32, pos, pos + DEATH_EXPLOSION_WIDTH), 0, self.led_string.len() - 1);
+                        for i in start..stop {
+                            self.led_string[i].set_rgb([255, brightness, brightness]);
+                        }
+                        if time < (start_time + DEATH_EXPLOSION_DUR) {
+                            State::Death{level, stage, start_time}
+                        } else {
+                            State::Death{level, stage: DeathStage::Particles, start_time: time}
+                        }
+                    },
+                    DeathStage::Particles => {
+                        if self.world.cycle_particles(&mut self.led_string, true, 0) {
+                            State::Death{level, stage, start_time}
+                        } else {
+                            if level == 0 {
+                                self.world.player_set_lives(PLAYER_DEFAULT_LIVES);
+                            }
+                            State::Lives{level, start_time: time}
+                        }
+                    }
+                }
+            },
+            State::Lives{level, start_time} => {
+                if self.world.player_lives() == 0 {
+                    //State::Starting{stage: StartStage::Wipeup, start_time: time}
+                    State::GameOver{stage: GameOverStage::Spread, start_time: time}
+                } else {
+                    self.led_string.clear();
+                    // Render lives
+                    let mut pos = 0;
+                    for _ in 0..self.world.player_lives() {
+                        for _ in 0..4 {
+                            self.led_string[pos].set_rgb([0, 255, 0]);
+                            pos += 1;
+                        }
+                        pos += 1;
+                    }
+                    if time < (start_time + LIVES_DISPLAY_DUR) {
+                        State::Lives{level, start_time}
+                    } else {
+                        self.build_level(level, time);
+                        State::Playing{level, timeout: time}
+                    }
+                }
+            },
+            State::GameOver{stage, start_time} => {
+                self.led_string.clear();
+                match stage {
+                    GameOverStage::Spread => {
+                        let pos = self.led_string.vtor(self.world.player_position());
+                        let start = range_map((time - start_time) as i32, 0, GAMEOVER_SPREAD_DUR as i32, pos, 0);
+                        let stop = range_map((time - start_time) as i32, 0, GAMEOVER_SPREAD_DUR as i32, pos, self.led_string.len() - 1);
+                        //println!("t{} d{} p{} strt{} stop{} ", (time - start_time) as i32, GAMEOVER_SPREAD_DUR as i32, pos, start, stop);
+                        for i in start..stop {
+                            self.led_string[i].set_rgb([255, 0, 0]);
+                        }
+                        if time < (start_time + GAMEOVER_SPREAD_DUR) {
+                            State::GameOver{stage, start_time}
+                        } else {
+                            State::GameOver{stage: GameOverStage::Fade, start_time: time}
+                        }
+                    },
+                    GameOverStage::Fade => {
+                        let stop = range_map((time - start_time) as i32, GAMEOVER_FADE_DUR as i32, 0, 0, self.led_string.len() - 1).max(0);
+                        let brightness = range_map(time - start_time, 0, GAMEOVER_FADE_DUR, 255, 0) as u8;
+                        for i in 0..stop {
+                            self.led_string[i].set_rgb([brightness, 0, 0]);
+                        }
+                        if time < (start_time + GAMEOVER_FADE_DUR) {
+                            State::GameOver{stage, start_time}
+                        } else {
+                            self.world.player_set_lives(PLAYER_DEFAULT_LIVES);
+                            self.set_game_loop(0);
+                            self.build_level(0, time);
+                            State::Playing{level: 0, timeout: time}
+                        }
+                    }
+                }
+            },
+            State::Win{level, stage, start_time} => {
+                self.led_string.clear();
+                match stage {
+                    WinStage::Fill => {
+                        let n = range_map((time - start_time) as i32, 0, WIN_FILL_DUR as i32, self.led_string.len(), 0);
+                        for i in n..self.led_string.len() {
+                            self.led_string[i].set_rgb([0, 255, 0]);
+                        }
+                        if time < (start_time + WIN_FILL_DUR) {
+                            State::Win{level, stage, start_time}
+                        } else {
+                            State::Win{level, stage: WinStage::Clear, start_time: time}
+                        }
+                    },
+                    WinStage::Clear => {
+                        let n = range_map((time - start_time) as i32, 0, WIN_CLEAR_DUR as i32, self.led_string.len(), 0);
+                        for i in 0..n {
+                            self.led_string[i].set_rgb([0, 255, 0]);
+                        }
+                        if time < (start_time + WIN_CLEAR_DUR) {
+                            State::Win{level, stage, start_time}
+                        } else {
+                            State::Win{level, stage: WinStage::Off, start_time: time}
+                        }
+                    },
+                    WinStage::Off => {
+                        self.led_string[0].set_rgb([0, 255, 0]);
+                        if time < (start_time + WIN_OFF_DUR) {
+                            State::Win{level, stage, start_time}
+                        } else {
+                            self.build_level(level + 1, time);
+                            State::Playing{level: level + 1, timeout: time}
+                        }
+                    }
+                }
+            },
+            State::BossKilled{stage, start_time} => {
+                self.led_string.clear();
+                match stage {
+                    BossKilledStage::Rainbow{hue} => {
+                        let mut h = hue;
+                        for i in 0..self.led_string.len() {
+                            self.led_string[i].set_hsv(h, 240, 255);
+                            h = h.wrapping_add(7);
+                        }
+                        if random8() < 200 {
+                            let pos = random16lim(self.led_string.len() as u16) as i32;
+                            self.led_string[pos].set_rgb([255, 255, 255]);
+                        }
+                        if time < (start_time + BOSSKILLED_RAINBOW_DUR) {
+                            State::BossKilled{stage: BossKilledStage::Rainbow{hue: hue.wrapping_add(1)}, start_time}
+                        } else {
+                            State::BossKilled{stage: BossKilledStage::Wipe, start_time: time}
+                        }
+                    },
+                    BossKilledStage::Wipe => {
+                        let n = range_map((time - start_time) as i32, 0, BOSSKILLED_WIPE_DUR as i32, self.led_string.len(), 0);
+                        for i in 0..n {
+                            let h = sinu8((i.wrapping_mul(10).wrapping_add(time as i32).wrapping_mul(500/255) % 0xFF) as u8).wrapping_add(1);
+                            self.led_string[i].set_hsv(h, 255, 50);
+                        }
+                        if time < (start_time + BOSSKILLED_WIPE_DUR) {
+                            State::BossKilled{stage, start_time}
+                        } else {
+                            // New game+, start over a bit harder
+                            self.set_game_loop(self.game_loop + 1);
+                            self.build_level(0, time);
+                            State::Playing{level: 0, timeout: time}
+                        }
+                    }
+                }
+            }
+        };
+
+        if self.status() != status {
+            self.log.emit(time, Event::State{status: self.status()});
+        }
+        if self.screensaver.mode != mode {
+            self.log.emit(time, Event::AttractMode{mode: self.screensaver.mode});
+        }
+    }
+
+    /// Streams a snapshot of the game at `time`, the time of the last `cycle`,
+    /// see `snapshot` for what goes in.
+    pub fn save_snapshot(&self, time: u32, out: &mut dyn FnMut(u8)) {
+        let mut w = Writer::new(out);
+        w.u32(time);
+        w.u16(random16_get_seed());
+        w.u32(self.game_loop);
+        w.bool(self.endless);
+        w.u16(self.led_string.len() as u16);
+        w.u32(self.screensaver.mode);
+        self.state.save(&mut w);
+        self.world.save(&mut w);
+        w.finish();
+    }
+
+    /// Continues the game from a snapshot, returns the time it was taken at.
+    /// A broken snapshot leaves the game alone.
+    pub fn load_snapshot(&mut self, data: &[u8]) -> Result<u32, SnapshotError> {
+        let mut r = Reader::new(data)?;
+        let time = r.u32()?;
+        let seed = r.u16()?;
+        let mut twang = self.clone();
+        twang.game_loop = r.u32()?;
+        twang.endless = r.bool()?;
+        let len = r.u16()? as usize;
+        if !(1..=LED_STRING_MAX_LENGTH).contains(&len) {
+            return Err(SnapshotError::Invalid);
+        }
+        twang.led_string.set_len(len);
+        twang.screensaver.mode = r.u32()?;
+        twang.state.load(&mut r)?;
+        twang.world.load(&mut r)?;
+        r.finish()?;
+        random16_set_seed(seed);
+        *self = twang;
+        Ok(time)
+    }
+
+    /// The frame time profiler, the application measures its own sections with it.
+    pub fn profiler(&mut self) -> &mut Profiler {
+        &mut self.profiler
+    }
+
+    pub fn get_led(&mut self, i: usize) -> [u8; 3] {
+        let led = self.led_string.get_raw(i as i32);
+        [led.r, led.g, led.b]
+    }
+
+    pub fn get_led_len(&mut self) -> usize {
+        self.led_string.len() as usize
+    }
+
+    /// Changes the strip length while running, panics outside 1..=LED_STRING_MAX_LENGTH.
+    pub fn set_led_len(&mut self, len: usize) {
+        self.led_string.set_len(len);
+    }
+
+    fn set_game_loop(&mut self, game_loop: u32) {
+        self.game_loop = game_loop;
+        // The world caps the difficulty
+        self.world.set_difficulty(game_loop.saturating_mul(LOOP_DIFFICULTY_STEP).saturating_add(100));
+    }
+
+    fn build_generated_level(&mut self, level: u32, time: u32) {
+        let difficulty = level.saturating_add(self.game_loop.saturating_mul(LEVEL_COUNT));
+        let seed = GENERATOR_SEED ^ (difficulty as u16).wrapping_mul(7919);
+        LevelPlan::generate(seed, difficulty, self.world.difficulty()).build(&mut self.world, time);
+    }
+
+    fn build_level(&mut self, level: u32, time: u32) {
+        self.world.reset();
+
+        // Only level 0 starts with the player at a different position than 0
+        if level == 0 && !self.endless {
+            self.world.spawn_player(200);
+        } else {
+            self.world.spawn_player(0);
+        }
+
+        if self.endless {
+            self.build_generated_level(level, time);
+            return;
+        }
+
+        // Setup the rest of the level
+        match level {
+            0 => { // One enemy, kill it
+                self.world.spawn_enemy(500, 0, 0, EnemyKind::Basic);
+            },
+            1 => { // One enemy, kill it, it is coming for you
+                self.world.spawn_enemy(999, -1, 0, EnemyKind::Basic);
+            },
+            2 => { // Spawning enemies at exit every 3 seconds
+                self.world.spawn_spawner(time, 999, 3000, -2, 0, EnemyKind::Basic);
+            },
+            3 => { // Lava intro
+                self.world.spawn_lava(time, 400, 490, 2000, 2000, 0, false);
+                self.world.spawn_enemy(350, -1, 0, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 999, 5500, -3, 0, EnemyKind::Basic)
+            },
+            4 => { // Two sin enemies
+                self.world.spawn_enemy(700, 3, 275, EnemyKind::Basic);
+                self.world.spawn_enemy(500, 2, 250, EnemyKind::Basic);
+            },
+            5 => { // Conveyor
+                self.world.spawn_conveyor(100, 600, -6);
+                self.world.spawn_enemy(800, 0, 0, EnemyKind::Basic);
+            },
+            6 => { // Drainage
+                self.world.spawn_conveyor(100, 600, 1);
+                self.world.spawn_conveyor(600, 999, -1);
+                self.world.spawn_enemy(600, 0, 0, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 999, 5500, -3, 0, EnemyKind::Basic);
+            },
+            7 => { // Enemy swarm
+                self.world.spawn_enemy(700, 3, 275, EnemyKind::Basic);
+                self.world.spawn_enemy(500, 2, 250, EnemyKind::Basic);
+                self.world.spawn_enemy(600, 3, 200, EnemyKind::Basic);
+                self.world.spawn_enemy(800, 2, 350, EnemyKind::Basic);
+                self.world.spawn_enemy(400, 3, 150, EnemyKind::Basic);
+                self.world.spawn_enemy(450, 2, 400, EnemyKind::Basic);
+            },
+            8 => { // Sin enemy #2 practice (slow conveyor)
+                self.world.spawn_enemy(700, 7, 275, EnemyKind::Basic);
+                self.world.spawn_enemy(500, 5, 250, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 999, 5500, -4, 3000, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 0, 5500, 5, 10000, EnemyKind::Basic);
+                self.world.spawn_conveyor(100, 900, -4);
+            },
+            9 => { // Conveyor of enemies
+                self.world.spawn_conveyor(50, 998, 6);
+                self.world.spawn_enemy(300, 0, 0, EnemyKind::Basic);
+                self.world.spawn_enemy(400, 0, 0, EnemyKind::Basic);
+                self.world.spawn_enemy(500, 0, 0, EnemyKind::Basic);
+                self.world.spawn_enemy(600, 0, 0, EnemyKind::Basic);
+                self.world.spawn_enemy(700, 0, 0, EnemyKind::Basic);
+                self.world.spawn_enemy(800, 0, 0, EnemyKind::Basic);
+                self.world.spawn_enemy(900, 0, 0, EnemyKind::Basic);
+            },
+            10 => { // Lava run
+                self.world.spawn_lava(time, 195, 300, 2000, 2000, 0, false);
+                self.world.spawn_lava(time, 400, 500, 2000, 2000, 0, false);
+                self.world.spawn_lava(time, 600, 700, 2000, 2000, 0, false);
+                self.world.spawn_spawner(time, 999, 3800, 4, 0, EnemyKind::Basic);
+            },
+            11 => { // Sin enemy #2 (fast conveyor)
+                self.world.spawn_enemy(800, -7, 275, EnemyKind::Basic);
+                self.world.spawn_enemy(700, -7, 275, EnemyKind::Basic);
+                self.world.spawn_enemy(500, -5, 250, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 999, 3000, -4, 3000, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 0, 5500, 5, 10000, EnemyKind::Basic);
+                self.world.spawn_conveyor(100, 900, 6);
+            },
+            12 => { // less lava, more enemies
+                self.world.spawn_lava(time, 350, 455, 2000, 2000, 0, false);
+                self.world.spawn_lava(time, 660, 760, 2000, 2000, 0, false);
+                self.world.spawn_spawner(time, 999, 3800, -4, 270, EnemyKind::Basic);
+                self.world.spawn_enemy(800, 0, 0, EnemyKind::Basic);
+            },
+            13 => { // pushed towards lava
+                self.world.spawn_conveyor(100, 800, 1);
+                self.world.spawn_lava(time, 800, 850, 1000, 2000, 0, false);
+                self.world.spawn_spawner(time, 999, 2000, -4, 0, EnemyKind::Basic);
+            },
+            14 => { // quick lava
+                self.world.spawn_spawner(time, 0, 2300, 6, 7000, EnemyKind::Basic);
+                self.world.spawn_lava(time, 200, 400, 1000, 2000, 0, false);
+                self.world.spawn_lava(time, 600, 800, 1000, 2000, 0, false);
+                self.world.spawn_spawner(time,999, 2500, -6, 1000, EnemyKind::Basic);
+            },
+            15 => { // spawn train;
+                self.world.spawn_spawner(time, 900, 1300, -2, 0, EnemyKind::Basic);
+            },
+            16 => { // spawn train skinny attack width, charge it up to widen;
+                self.world.player_set_attack_width(32);
+                self.world.player_set_attack_charge(800, 40);
+                self.world.spawn_spawner(time, 900, 1800, -2, 0, EnemyKind::Basic);
+            },
+            17 => { // evil fast split spawner, quicker attacks to keep up
+                self.world.player_set_attack_cooldown(100);
+                self.world.spawn_spawner(time, 550, 1500, -2, 0, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 550, 1500, 2, 0, EnemyKind::Basic);
+            },
+            18 => { // split spawner with exit blocking lava
+                self.world.spawn_spawner(time, 500, 1200, -2, 0, EnemyKind::Basic);
+                self.world.spawn_spawner(time, 500, 1200, 2, 0, EnemyKind::Basic);
+                self.world.spawn_lava(time, 900, 950, 2200, 800, 2000, false);
+            },
+            19 => { // Lava flowing along the track, strike it to put it out
+                self.world.spawn_lava_flow(300, 30, 2, 200, 500);
+                self.world.spawn_lava_flow(700, 30, -3, 550, 900);
+                self.world.spawn_spawner(time, 999, 4000, -2, 0, EnemyKind::Basic);
+            },
+            20 => { // Portal over a lava pool, the enemies come through it too
+                self.world.spawn_lava(time, 450, 550, 2000, 2000, 0, false);
+                self.world.spawn_portal(400, 600);
+                self.world.spawn_spawner(time, 999, 3000, -2, 0, EnemyKind::Basic);
+            },
+            21 => { // Chasers homing in, and one only visible up close
+                self.world.spawn_enemy(600, 2, 0, EnemyKind::Chaser);
+                self.world.spawn_enemy(450, 2, 300, EnemyKind::Invisible);
+                self.world.spawn_spawner(time, 999, 4000, -2, 2000, EnemyKind::Chaser);
+            },
+            22 => { // Enemies taking two hits, and ones splitting in two
+                self.world.spawn_enemy(400, 0, 0, EnemyKind::Shielded);
+                self.world.spawn_enemy(800, -1, 0, EnemyKind::Splitter);
+            },
+            23 => { // Fire boss, on to the last level once it's beaten
+                self.world.spawn_boss(time, BossKind::Fire);
+            },
+            24 => {
+                self.world.spawn_boss(time, BossKind::Dungeon);
+            },
+            _ => { // Out of handcrafted levels, make one up
+                self.build_generated_level(level, time);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod golden;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn killing_the_boss_loops_the_game() {
+        let mut twang = Twang::new(Config::default());
+        let mut time = 1000;
+        twang.start_level(LEVEL_COUNT - 1, time);
+        for _ in 0..BossKind::Dungeon.phases().len() {
+            twang.world.hit_boss(time);
+        }
+        twang.cycle(0, false, time);
+        assert_eq!(twang.status(), Status::BossKilled);
+        while twang.status() == Status::BossKilled {
+            assert!(time < 1000 + BOSSKILLED_RAINBOW_DUR + BOSSKILLED_WIPE_DUR + 100);
+            time += 16;
+            twang.cycle(0, false, time);
+        }
+        assert_eq!(twang.status(), Status::Playing{level: 0});
+        assert_eq!(twang.game_loop, 1);
+    }
+
+    #[test]
+    fn killing_an_earlier_boss_moves_on() {
+        let mut twang = Twang::new(Config::default());
+        let time = 1000;
+        twang.start_level(LEVEL_COUNT - 2, time);
+        for _ in 0..BossKind::Fire.phases().len() {
+            twang.world.hit_boss(time);
+        }
+        twang.cycle(0, false, time);
+        assert_eq!(twang.status(), Status::Win{level: LEVEL_COUNT - 2});
+        assert_eq!(twang.game_loop, 0);
+    }
+
+    #[test]
+    fn difficulty_scaling_is_capped() {
+        let mut twang = Twang::new(Config::default());
+        twang.set_game_loop(u32::MAX);
+        // A handcrafted conveyor level and a generated one
+        for level in [5, LEVEL_COUNT + 5] {
+            twang.start_level(level, 1000);
+            twang.cycle(0, false, 1016);
+            assert_eq!(twang.status(), Status::Playing{level});
+            assert_eq!(twang.world.difficulty(), world::MAX_DIFFICULTY);
+        }
+        twang.start_level(5, 1000);
+        let mut speeds = Vec::new();
+        twang.inspect(1000, &mut |e| if let Entity::Conveyor{speed, ..} = e {
+            speeds.push(speed);
+        });
+        assert_eq!(speeds, [world::scale_conveyor_speed(-6, world::MAX_DIFFICULTY)]);
+        assert_eq!(world::scale_period(2000, world::MAX_DIFFICULTY), 1000);
+        assert_eq!(world::scale_period(1000, 1000), 500);
+        assert_eq!(world::scale_period(300, 1000), 300);
+        assert_eq!(world::scale_speed(-3, world::MAX_DIFFICULTY), -6);
+    }
+}
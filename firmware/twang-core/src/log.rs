@@ -0,0 +1,310 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Leveled and rate limited game event log.
+//!
+//! The game queues structured events while it runs, the application drains
+//! them into a `Logger` backend once per frame.
+
+use core::fmt;
+
+use super::Status;
+
+const LOG_QUEUE_SIZE: usize = 16;   // Events kept until the application drains them
+const LOG_RATE_PERIOD: u32 = 1000;  // Rate limit window in ms
+const LOG_RATE_LIMIT: u32 = 10;     // Events let through per window
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Error => "ERR",
+            Level::Warn => "WRN",
+            Level::Info => "INF",
+            Level::Debug => "DBG",
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Event {
+    State{status: Status},      // The game moved on to a new state
+    AttractMode{mode: u32},     // The screensaver switched its animation
+    Dropped{count: u32},        // Events lost to the rate limit or a full queue
+}
+
+impl Event {
+    pub fn level(&self) -> Level {
+        match self {
+            Event::State{..} => Level::Info,
+            Event::AttractMode{..} => Level::Debug,
+            Event::Dropped{..} => Level::Warn,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::State{status} => write!(f, "state {:?}", status),
+            Event::AttractMode{mode} => write!(f, "attract mode {}", mode),
+            Event::Dropped{count} => write!(f, "dropped {} events", count),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Record {
+    pub time: u32,
+    pub level: Level,
+    pub event: Event,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:8}] {} {}", self.time, self.level, self.event)
+    }
+}
+
+/// Log backend, gets the records the game emitted.
+pub trait Logger {
+    fn log(&mut self, record: &Record);
+}
+
+/// Sends the records to two backends, for example the console and a ring
+/// buffer for post-mortem dumps.
+impl<A: Logger, B: Logger> Logger for (A, B) {
+    fn log(&mut self, record: &Record) {
+        self.0.log(record);
+        self.1.log(record);
+    }
+}
+
+impl<T: Logger + ?Sized> Logger for &mut T {
+    fn log(&mut self, record: &Record) {
+        (**self).log(record);
+    }
+}
+
+/// Keeps the last `N` records around.
+#[derive(Clone)]
+pub struct RingLogger<const N: usize> {
+    records: [Option<Record>; N],
+    next: usize,
+}
+
+impl<const N: usize> Default for RingLogger<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> RingLogger<N> {
+    pub const fn new() -> Self {
+        Self {
+            records: [None; N],
+            next: 0,
+        }
+    }
+
+    /// Records from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &Record> {
+        let (newer, older) = self.records.split_at(self.next);
+        older.iter().chain(newer.iter()).flatten()
+    }
+
+    pub fn clear(&mut self) {
+        self.records = [None; N];
+        self.next = 0;
+    }
+}
+
+impl<const N: usize> Logger for RingLogger<N> {
+    fn log(&mut self, record: &Record) {
+        if N == 0 {
+            return;
+        }
+        self.records[self.next] = Some(*record);
+        self.next = (self.next + 1) % N;
+    }
+}
+
+/// Prints the records on stdout.
+#[cfg(feature = "std")]
+pub struct StdoutLogger;
+
+#[cfg(feature = "std")]
+impl Logger for StdoutLogger {
+    fn log(&mut self, record: &Record) {
+        std::println!("{}", record);
+    }
+}
+
+/// Event queue of the game, filters by level and limits the rate.
+#[derive(Clone)]
+pub struct Log {
+    queue: RingLogger<LOG_QUEUE_SIZE>,
+    queued: usize,
+    max_level: Level,
+    window_start: u32,
+    window_count: u32,
+    dropped: u32,
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Log {
+    pub fn new() -> Self {
+        Self {
+            queue: RingLogger::new(),
+            queued: 0,
+            max_level: Level::Info,
+            window_start: 0,
+            window_count: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn set_level(&mut self, level: Level) {
+        self.max_level = level;
+    }
+
+    pub fn emit(&mut self, time: u32, event: Event) {
+        let level = event.level();
+        if level > self.max_level {
+            return;
+        }
+        if time >= self.window_start + LOG_RATE_PERIOD {
+            self.window_start = time;
+            self.window_count = 0;
+            // Keep counting if there is no room to report it yet
+            if self.dropped > 0 && self.queued < LOG_QUEUE_SIZE {
+                let count = self.dropped;
+                self.dropped = 0;
+                self.push(Record{time, level: Level::Warn, event: Event::Dropped{count}});
+            }
+        }
+        if self.window_count >= LOG_RATE_LIMIT {
+            self.dropped += 1;
+            return;
+        }
+        self.window_count += 1;
+        self.push(Record{time, level, event});
+    }
+
+    fn push(&mut self, record: Record) {
+        if self.queued == LOG_QUEUE_SIZE {
+            self.dropped += 1;
+            return;
+        }
+        self.queue.log(&record);
+        self.queued += 1;
+    }
+
+    /// Hands the queued records to the backend, oldest first.
+    pub fn drain(&mut self, logger: &mut dyn Logger) {
+        for record in self.queue.iter() {
+            logger.log(record);
+        }
+        self.queue.clear();
+        self.queued = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct VecLogger(Vec<Record>);
+
+    impl Logger for VecLogger {
+        fn log(&mut self, record: &Record) {
+            self.0.push(*record);
+        }
+    }
+
+    fn drain(log: &mut Log) -> Vec<Event> {
+        let mut logger = VecLogger::default();
+        log.drain(&mut logger);
+        logger.0.iter().map(|r| r.event).collect()
+    }
+
+    #[test]
+    fn filters_by_level() {
+        let mut log = Log::new();
+        log.emit(0, Event::AttractMode{mode: 1});
+        log.emit(0, Event::State{status: Status::GameOver});
+        assert_eq!(drain(&mut log), [Event::State{status: Status::GameOver}]);
+        log.set_level(Level::Debug);
+        log.emit(0, Event::AttractMode{mode: 1});
+        assert_eq!(drain(&mut log), [Event::AttractMode{mode: 1}]);
+    }
+
+    #[test]
+    fn limits_the_rate() {
+        let mut log = Log::new();
+        for i in 0..LOG_RATE_LIMIT + 5 {
+            log.emit(0, Event::State{status: Status::Playing{level: i}});
+        }
+        assert_eq!(drain(&mut log).len(), LOG_RATE_LIMIT as usize);
+        log.emit(LOG_RATE_PERIOD, Event::State{status: Status::GameOver});
+        assert_eq!(drain(&mut log), [Event::Dropped{count: 5}, Event::State{status: Status::GameOver}]);
+    }
+
+    #[test]
+    fn counts_a_full_queue_as_dropped() {
+        let mut log = Log::new();
+        for i in 0..(LOG_QUEUE_SIZE as u32 + 3) {
+            log.emit(i * LOG_RATE_PERIOD, Event::State{status: Status::Playing{level: i}});
+        }
+        let events = drain(&mut log);
+        assert_eq!(events.len(), LOG_QUEUE_SIZE);
+        assert_eq!(events[0], Event::State{status: Status::Playing{level: 0}});
+        log.emit(100 * LOG_RATE_PERIOD, Event::State{status: Status::GameOver});
+        assert_eq!(drain(&mut log)[0], Event::Dropped{count: 3});
+    }
+
+    #[test]
+    fn ring_keeps_the_newest() {
+        let mut ring = RingLogger::<3>::new();
+        for level in 0..5 {
+            ring.log(&Record{time: level, level: Level::Info, event: Event::State{status: Status::Playing{level}}});
+        }
+        let times: Vec<u32> = ring.iter().map(|r| r.time).collect();
+        assert_eq!(times, [2, 3, 4]);
+    }
+}
@@ -23,6 +23,7 @@
  */
 
 use super::{led_string::{LEDString, LED}, rand::random8};
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
 
 #[derive(Copy, Clone, Debug)]
 pub struct Particle {
@@ -107,6 +108,32 @@ impl Particle {
         self.speed = ((random8() as i32) - 128) as i8;
         self.power = 255;
         self.alive = true;
-        self.life = 220 - (self.speed as i32).abs() as u8;
+        self.life = 220 - (self.speed as i32).unsigned_abs() as u8;
     }
-}
\ No newline at end of file
+}
+
+impl Save for Particle {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.position);
+        w.u8(self.power);
+        w.u8(self.life);
+        w.u8(self.speed as u8);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.position = r.i32()?;
+        self.power = r.u8()?;
+        self.life = r.u8()?;
+        self.speed = r.u8()? as i8;
+        Ok(())
+    }
+}
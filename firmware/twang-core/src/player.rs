@@ -0,0 +1,587 @@
+/*
+ * Copyright (c) 2020-2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::boss::Boss;
+use super::conveyor::Conveyor;
+use super::lava::Lava;
+use super::lava_flow::LavaFlow;
+use super::portal::Portal;
+use super::led_string::{LEDString, LED_STRING_VLENGTH};
+use super::utils::range_map;
+use super::enemy::Enemy;
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+
+const ATTACK_WIDTH: i32 = 70;
+const ATTACK_DURATION: u32 = 500;
+const ATTACK_COOLDOWN: u32 = 0; // No cooldown unless a level asks for one
+const ATTACK_CHARGE_DURATION: u32 = 0; // Charging is disabled by default
+const ATTACK_CHARGE_WIDTH: i32 = 0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Player {
+    pub position: i32,
+    prev_position: i32,
+    direction: i32,
+    pub attack_width: i32,          // Base attack width
+    pub attack_cur_width: i32,      // Width of the currently running attack
+    pub attacking: bool,
+    pub attacking_millis: u32,      // Start of the running attack, tells attacks apart
+    attack_duration: u32,
+    pub attack_cooldown: u32,       // Time after an attack before the next one can start
+    cooldown_millis: u32,           // Time when the cooldown expires
+    pub attack_charge_duration: u32,// Time to fully charge an attack, 0 disables charging
+    pub attack_charge_width: i32,   // Width added to a fully charged attack
+    charging: bool,
+    charging_millis: u32,
+    pub speed: i32,
+    pub lives: u8,
+    pub alive: bool,
+    pub god: bool,                  // Nothing can kill the player, for debugging
+}
+
+impl Player {
+    pub fn new(direction: i32) -> Self {
+        Self {
+            position: 0,
+            prev_position: 0,
+            direction,
+            attack_width: ATTACK_WIDTH,
+            attack_cur_width: ATTACK_WIDTH,
+            attacking: false,
+            attacking_millis: 0,
+            attack_duration: ATTACK_DURATION,
+            attack_cooldown: ATTACK_COOLDOWN,
+            cooldown_millis: 0,
+            attack_charge_duration: ATTACK_CHARGE_DURATION,
+            attack_charge_width: ATTACK_CHARGE_WIDTH,
+            charging: false,
+            charging_millis: 0,
+            speed: 0,
+            lives: 0,
+            alive: false,
+            god: false,
+        }
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString, time: u32) {
+        if !self.alive {
+            return;
+        }
+        if !self.attacking && !self.charging && !self.cooling_down(time) {
+            let pos = led_string.vtor(self.position);
+            led_string[pos].set_rgb([0, 255, 0]);
+        } else {
+            self.draw_attack(led_string, time);
+        }
+    }
+
+    fn draw_attack(&self, led_string: &mut LEDString, time: u32) {
+        let pos = led_string.vtor(self.position);
+        if self.charging {
+            // Draw the charge as a growing purple bar around the player
+            let width = self.charge_width(time);
+            let from = led_string.vtor(self.position - (width / 2));
+            let to = led_string.vtor(self.position + (width / 2));
+            for i in from..(to + 1) {
+                led_string[i].set_rgb([20, 0, 40]);
+            }
+            led_string[pos].set_rgb([0, 255, 0]);
+            return;
+        }
+        if !self.attacking {
+            // Cooldown, the player is dim until it can attack again
+            let n = range_map(time - self.attacking_millis - self.attack_duration, 0, self.attack_cooldown.max(1), 40, 200) as u8;
+            led_string[pos].set_rgb([0, n, 0]);
+            return;
+        }
+        // Fade value
+        let mut n = range_map(time - self.attacking_millis, 0, self.attack_duration, 100, 5) as u8;
+        // Draw blue attack bar
+        let from = led_string.vtor(self.position - (self.attack_cur_width / 2)) + 1;
+        let to = led_string.vtor(self.position + (self.attack_cur_width / 2));
+        for i in from..to {
+            if i >= 0 && i < led_string.len() {
+                led_string[i].set_rgb([0, 0, n]);
+            }
+        }
+        // Draw player as white at the beginning of the attack and then back to green
+        if n > 90 {
+            n = 255;
+            led_string[pos].set_rgb([255, 255, 255]);
+        } else {
+            n = 0;
+            led_string[pos].set_rgb([0, 255, 0]);
+        }
+        led_string[from - 1].set_rgb([n, n, 255]);
+        led_string[to].set_rgb([n, n, 255]);
+    }
+
+    pub fn tick(&mut self, led_string: &LEDString, time: u32) {
+        if !self.alive {
+            return;
+        }
+        self.prev_position = self.position;
+        if self.attacking {
+            if self.attacking_millis + self.attack_duration < time {
+                self.attacking = false;
+            }
+            return;
+        }
+        let amount = self.speed * self.direction;
+        let len = led_string.vlen();
+        self.position += amount;
+        if self.position < 0 {
+            self.position = 0
+        } else if self.position >= len {
+            self.position = len - 1
+        }
+    }
+
+    pub fn collide_enemy(&mut self, enemy: &Enemy) {
+        if !enemy.alive {
+            return;
+        }
+        if ((enemy.player_side == 1) && (self.position >= enemy.position)) ||
+            ((enemy.player_side == -1) && (self.position <= enemy.position)) {
+            self.die();
+        }
+    }
+
+    pub fn collide_lava(&mut self, lava: &Lava) {
+        if !lava.alive || !lava.state {
+            return;
+        }
+        if (self.position >= lava.pos_start) &&
+            (self.position < lava.pos_end) {
+            self.die();
+        }
+    }
+
+    pub fn collide_lava_flow(&mut self, flow: &LavaFlow) {
+        if !flow.alive || !flow.hot {
+            return;
+        }
+        if (self.position >= flow.pos_start) &&
+            (self.position <= flow.pos_end) {
+            self.die();
+        }
+    }
+
+    pub fn collide_conveyor(&mut self, conveyor: &Conveyor) {
+        if !conveyor.alive {
+            return;
+        }
+        if (self.position >= conveyor.pos_start) &&
+            (self.position < conveyor.pos_end) {
+                self.position += conveyor.speed;
+                self.stay_on_strip();
+        }
+    }
+
+    /// Keeps the player on the strip after being pushed or warped
+    fn stay_on_strip(&mut self) {
+        self.position = self.position.clamp(0, LED_STRING_VLENGTH as i32 - 1);
+    }
+
+    /// Returns true if the player was warped
+    pub fn collide_portal(&mut self, portal: &Portal) -> bool {
+        if !portal.alive {
+            return false;
+        }
+        match portal.warp(self.prev_position, self.position) {
+            Some(position) => {
+                self.position = position;
+                self.stay_on_strip();
+                self.prev_position = self.position;
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn collide_boss(&mut self, boss: &Boss) {
+        if !boss.alive {
+            return;
+        }
+        if ((self.position > boss.start) &&
+        (self.position < boss.stop)) ||
+        boss.lava_hit(self.position) {
+            self.die();
+        }
+    }
+
+    pub fn die(&mut self) {
+        // We can run into more than one thing in the same frame
+        if !self.alive || self.god {
+            return;
+        }
+        self.lives = self.lives.saturating_sub(1);
+        self.alive = false;
+    }
+
+    /// Handles the fire input. Without charging an attack starts as soon as
+    /// the cooldown expired. With charging enabled, holding fire charges the
+    /// attack and releasing it strikes with the charged width.
+    pub fn attack(&mut self, fire: bool, time: u32) {
+        if self.attacking || self.cooling_down(time) {
+            return;
+        }
+        if self.attack_charge_duration == 0 {
+            if fire {
+                self.strike(self.attack_width, time);
+            }
+            return;
+        }
+        if fire {
+            if !self.charging {
+                self.charging = true;
+                self.charging_millis = time;
+            }
+        } else if self.charging {
+            self.charging = false;
+            self.strike(self.charge_width(time), time);
+        }
+    }
+
+    fn strike(&mut self, width: i32, time: u32) {
+        self.attack_cur_width = width;
+        self.attacking_millis = time;
+        self.cooldown_millis = time + self.attack_duration + self.attack_cooldown;
+        self.attacking = true;
+    }
+
+    fn charge_width(&self, time: u32) -> i32 {
+        let held = (time - self.charging_millis).min(self.attack_charge_duration);
+        self.attack_width + range_map(held as i32, 0, self.attack_charge_duration as i32, 0, self.attack_charge_width)
+    }
+
+    fn cooling_down(&self, time: u32) -> bool {
+        time < self.cooldown_millis
+    }
+
+    pub fn inspect(&self, time: u32) -> Entity {
+        let reach = self.attack_cur_width / 2;
+        Entity::Player {
+            position: self.position,
+            lives: self.lives,
+            attacking: self.attacking,
+            attack_start: self.position - reach,
+            attack_end: self.position + reach,
+            attack_left: (self.attacking_millis + self.attack_duration).saturating_sub(time),
+            cooldown_left: self.cooldown_millis.saturating_sub(time),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.attack_width = ATTACK_WIDTH;
+        self.attack_cur_width = ATTACK_WIDTH;
+        self.attack_duration = ATTACK_DURATION;
+        self.attack_cooldown = ATTACK_COOLDOWN;
+        self.attack_charge_duration = ATTACK_CHARGE_DURATION;
+        self.attack_charge_width = ATTACK_CHARGE_WIDTH;
+        self.attacking = false;
+        self.charging = false;
+        self.cooldown_millis = 0;
+        self.alive = false;
+    }
+
+    pub fn spawn(&mut self, position: i32) {
+        self.position = position;
+        self.prev_position = position;
+        self.alive = true;
+    }
+}
+
+impl Save for Player {
+    fn save(&self, w: &mut Writer) {
+        w.i32(self.position);
+        w.i32(self.prev_position);
+        w.i32(self.direction);
+        w.i32(self.attack_width);
+        w.i32(self.attack_cur_width);
+        w.bool(self.attacking);
+        w.u32(self.attacking_millis);
+        w.u32(self.attack_duration);
+        w.u32(self.attack_cooldown);
+        w.u32(self.cooldown_millis);
+        w.u32(self.attack_charge_duration);
+        w.i32(self.attack_charge_width);
+        w.bool(self.charging);
+        w.u32(self.charging_millis);
+        w.i32(self.speed);
+        w.u8(self.lives);
+        w.bool(self.alive);
+        w.bool(self.god);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        self.position = r.i32()?;
+        self.prev_position = r.i32()?;
+        self.direction = r.i32()?;
+        self.attack_width = r.i32()?;
+        self.attack_cur_width = r.i32()?;
+        self.attacking = r.bool()?;
+        self.attacking_millis = r.u32()?;
+        self.attack_duration = r.u32()?;
+        self.attack_cooldown = r.u32()?;
+        self.cooldown_millis = r.u32()?;
+        self.attack_charge_duration = r.u32()?;
+        self.attack_charge_width = r.i32()?;
+        self.charging = r.bool()?;
+        self.charging_millis = r.u32()?;
+        self.speed = r.i32()?;
+        self.lives = r.u8()?;
+        self.alive = r.bool()?;
+        self.god = r.bool()?;
+        // Drawing divides by the durations, a live player has a life left
+        let strip = 0..LED_STRING_VLENGTH as i32;
+        if !strip.contains(&self.position) || !strip.contains(&self.prev_position) ||
+            self.attack_width < 0 || self.attack_cur_width < 0 || self.attack_charge_width < 0 ||
+            self.attack_duration == 0 || (self.charging && self.attack_charge_duration == 0) ||
+            (self.alive && self.lives == 0) {
+            return Err(SnapshotError::Invalid);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boss::BossKind;
+    use crate::enemy::EnemyKind;
+    use crate::spawner::Spawner;
+
+    fn player_at(position: i32) -> Player {
+        let mut player = Player::new(1);
+        player.lives = 3;
+        player.spawn(position);
+        player
+    }
+
+    #[test]
+    fn walks_and_stays_on_the_strip() {
+        let led_string = LEDString::new(144);
+        let mut player = player_at(0);
+        player.speed = 10;
+        player.tick(&led_string, 0);
+        assert_eq!(player.position, 10);
+        player.speed = -20;
+        player.tick(&led_string, 16);
+        assert_eq!(player.position, 0);
+        player.position = 995;
+        player.speed = 10;
+        player.tick(&led_string, 32);
+        assert_eq!(player.position, led_string.vlen() - 1);
+    }
+
+    #[test]
+    fn dies_walking_into_an_enemy() {
+        let mut enemy = Enemy::new();
+        enemy.spawn(500, 0, 0, EnemyKind::Basic);
+        enemy.player_side = 1;
+        let mut player = player_at(490);
+        player.collide_enemy(&enemy);
+        assert!(player.alive);
+        player.position = 500;
+        player.collide_enemy(&enemy);
+        assert!(!player.alive);
+        assert_eq!(player.lives, 2);
+    }
+
+    #[test]
+    fn dies_once_per_frame() {
+        let mut player = player_at(500);
+        player.lives = 1;
+        player.die();
+        player.die();
+        assert_eq!(player.lives, 0);
+    }
+
+    #[test]
+    fn god_mode_never_dies() {
+        let mut player = player_at(500);
+        player.god = true;
+        player.die();
+        assert!(player.alive);
+        assert_eq!(player.lives, 3);
+    }
+
+    #[test]
+    fn lava_only_kills_when_on() {
+        let mut lava = Lava::new();
+        lava.spawn(0, 400, 500, 1000, 1000, 0, false);
+        let mut player = player_at(450);
+        player.collide_lava(&lava);
+        assert!(player.alive);
+        lava.state = true;
+        player.position = 500;
+        player.collide_lava(&lava);
+        assert!(player.alive);
+        player.position = 400;
+        player.collide_lava(&lava);
+        assert!(!player.alive);
+    }
+
+    #[test]
+    fn lava_flow_only_kills_when_hot() {
+        let mut flow = LavaFlow::new();
+        flow.spawn(500, 20, 2, 400, 600);
+        let mut player = player_at(520);
+        flow.hot = false;
+        player.collide_lava_flow(&flow);
+        assert!(player.alive);
+        flow.hot = true;
+        player.collide_lava_flow(&flow);
+        assert!(!player.alive);
+    }
+
+    #[test]
+    fn conveyor_moves_the_player() {
+        let mut conveyor = Conveyor::new();
+        conveyor.spawn(100, 200, -3);
+        let mut player = player_at(150);
+        player.collide_conveyor(&conveyor);
+        assert_eq!(player.position, 147);
+        player.position = 200;
+        player.collide_conveyor(&conveyor);
+        assert_eq!(player.position, 200);
+    }
+
+    #[test]
+    fn conveyor_keeps_the_player_on_the_strip() {
+        let mut conveyor = Conveyor::new();
+        conveyor.spawn(900, 999, 8);
+        let mut player = player_at(995);
+        player.collide_conveyor(&conveyor);
+        assert_eq!(player.position, LED_STRING_VLENGTH as i32 - 1);
+    }
+
+    #[test]
+    fn portal_warps_when_crossed() {
+        let mut portal = Portal::new();
+        portal.spawn(400, 750);
+        let mut player = player_at(395);
+        player.position = 405;
+        assert!(player.collide_portal(&portal));
+        assert_eq!(player.position, 755);
+        // Standing next to the other end does not warp us back
+        assert!(!player.collide_portal(&portal));
+        assert_eq!(player.position, 755);
+    }
+
+    #[test]
+    fn boss_body_kills() {
+        let mut spawners = [Spawner::new(); 2];
+        let mut boss = Boss::new();
+        boss.spawn(0, BossKind::Dungeon, 100, &mut spawners);
+        let mut player = player_at(boss.start);
+        player.collide_boss(&boss);
+        assert!(player.alive);
+        player.position = boss.position;
+        player.collide_boss(&boss);
+        assert!(!player.alive);
+    }
+
+    #[test]
+    fn attack_repeats_without_a_cooldown() {
+        let mut player = player_at(500);
+        player.attack(true, 1000);
+        let led_string = LEDString::new(144);
+        player.tick(&led_string, 1000 + ATTACK_DURATION + 1);
+        assert!(!player.attacking);
+        player.attack(true, 1000 + ATTACK_DURATION + 1);
+        assert!(player.attacking);
+    }
+
+    #[test]
+    fn attack_has_a_cooldown() {
+        let mut player = player_at(500);
+        player.attack_cooldown = 300;
+        player.attack(true, 1000);
+        assert!(player.attacking);
+        assert_eq!(player.attack_cur_width, ATTACK_WIDTH);
+        let led_string = LEDString::new(144);
+        player.tick(&led_string, 1000 + ATTACK_DURATION + 1);
+        assert!(!player.attacking);
+        player.attack(true, 1000 + ATTACK_DURATION + 1);
+        assert!(!player.attacking);
+        player.attack(true, 1000 + ATTACK_DURATION + 300);
+        assert!(player.attacking);
+    }
+
+    #[test]
+    fn charged_attack_strikes_on_release() {
+        let mut player = player_at(500);
+        player.attack_charge_duration = 800;
+        player.attack_charge_width = 40;
+        player.attack(true, 1000);
+        assert!(!player.attacking);
+        player.attack(true, 1400);
+        player.attack(false, 1400);
+        assert!(player.attacking);
+        assert_eq!(player.attack_cur_width, ATTACK_WIDTH + 20);
+    }
+
+    #[test]
+    fn charge_is_capped() {
+        let mut player = player_at(500);
+        player.attack_charge_duration = 800;
+        player.attack_charge_width = 40;
+        player.attack(true, 1000);
+        player.attack(false, 5000);
+        assert_eq!(player.attack_cur_width, ATTACK_WIDTH + 40);
+    }
+
+    fn reload(player: &Player) -> Result<Player, SnapshotError> {
+        let mut data = std::vec::Vec::new();
+        let mut out = |b| data.push(b);
+        let mut w = Writer::new(&mut out);
+        player.save(&mut w);
+        w.finish();
+        let mut loaded = Player::new(1);
+        loaded.load(&mut Reader::new(&data).unwrap())?;
+        Ok(loaded)
+    }
+
+    #[test]
+    fn rejects_impossible_players() {
+        let player = player_at(500);
+        assert_eq!(reload(&player).unwrap().position, 500);
+        let mut charging = player;
+        charging.charging = true;
+        assert_eq!(reload(&charging).err(), Some(SnapshotError::Invalid));
+        let mut off_strip = player;
+        off_strip.position = LED_STRING_VLENGTH as i32;
+        assert_eq!(reload(&off_strip).err(), Some(SnapshotError::Invalid));
+        let mut negative = player;
+        negative.attack_width = -1;
+        assert_eq!(reload(&negative).err(), Some(SnapshotError::Invalid));
+        let mut no_lives = player;
+        no_lives.lives = 0;
+        assert_eq!(reload(&no_lives).err(), Some(SnapshotError::Invalid));
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::{led_string::LEDString, utils::sinu8};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+
+const PORTAL_WIDTH: i32 = 2;
+
+/// A pair of linked portals. Anything crossing one end comes out of the other
+/// end keeping its direction.
+#[derive(Clone, Copy, Debug)]
+pub struct Portal {
+    pub pos_a: i32,
+    pub pos_b: i32,
+    pub alive: bool,
+}
+
+impl Portal {
+    pub fn new() -> Self {
+        Self {
+            pos_a: 0,
+            pos_b: 0,
+            alive: false,
+        }
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString, time: u32) {
+        if !self.alive {
+            return;
+        }
+
+        // Both ends swirl through the same colors so the pair is recognizable
+        for &(pos, phase) in [(self.pos_a, 0), (self.pos_b, 128)].iter() {
+            let center = led_string.vtor(pos);
+            for i in -PORTAL_WIDTH..(PORTAL_WIDTH + 1) {
+                let n = ((time / 4) as i32 + i * 40 + phase) & 0xFF;
+                let v = sinu8(n as u8) / 2 + 20;
+                led_string[center + i].set_hsv(160 + (n / 8) as u8, 200, v);
+            }
+            led_string[center].set_rgb([255, 255, 255]);
+        }
+    }
+
+    /// Returns the warped position if moving from `prev` to `cur` crossed one
+    /// of the portal ends.
+    pub fn warp(&self, prev: i32, cur: i32) -> Option<i32> {
+        if !self.alive || prev == cur {
+            return None;
+        }
+        if Self::crossed(self.pos_a, prev, cur) {
+            Some(self.pos_b + (cur - self.pos_a))
+        } else if Self::crossed(self.pos_b, prev, cur) {
+            Some(self.pos_a + (cur - self.pos_b))
+        } else {
+            None
+        }
+    }
+
+    fn crossed(pos: i32, prev: i32, cur: i32) -> bool {
+        (prev < pos && cur >= pos) || (prev > pos && cur <= pos)
+    }
+
+    pub fn inspect(&self) -> Entity {
+        Entity::Portal{a: self.pos_a, b: self.pos_b}
+    }
+
+    pub fn reset(&mut self) {
+        self.alive = false;
+    }
+
+    pub fn spawn(&mut self, pos_a: i32, pos_b: i32) {
+        self.pos_a = pos_a;
+        self.pos_b = pos_b;
+        self.alive = true;
+    }
+}
+
+impl Save for Portal {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.pos_a);
+        w.i32(self.pos_b);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.pos_a = r.i32()?;
+        self.pos_b = r.i32()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Frame time profiler.
+//!
+//! The game measures its own sections of a frame, the application adds the
+//! sections around it (LED upload, waiting for the next frame). Times come
+//! from a clock function the application hands in, in microseconds. The
+//! clock may wrap at 2^32, it must not restart any earlier or sections
+//! running across the restart come out garbage. Without a clock the profiler
+//! does nothing.
+
+use core::fmt;
+
+pub const SECTION_COUNT: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Section {
+    Tick,       // World::tick, moving everything
+    Collide,    // World::collide
+    Draw,       // Clearing and drawing the LED string
+    Upload,     // Sending the LEDs to the hardware
+    Wait,       // Idle until the next frame
+}
+
+impl Section {
+    pub const ALL: [Section; SECTION_COUNT] = [Section::Tick, Section::Collide, Section::Draw, Section::Upload, Section::Wait];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Section::Tick => "tick",
+            Section::Collide => "collide",
+            Section::Draw => "draw",
+            Section::Upload => "upload",
+            Section::Wait => "wait",
+        }
+    }
+}
+
+/// Min, average and max duration of a section in microseconds.
+#[derive(Clone, Copy, Debug)]
+pub struct Stats {
+    pub min: u32,
+    pub max: u32,
+    total: u64,
+    count: u32,
+}
+
+impl Stats {
+    const fn new() -> Self {
+        Self {
+            min: u32::MAX,
+            max: 0,
+            total: 0,
+            count: 0,
+        }
+    }
+
+    fn add(&mut self, duration: u32) {
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+        self.total += duration as u64;
+        self.count += 1;
+    }
+
+    pub fn avg(&self) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.total / self.count as u64) as u32
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Prints `min/avg/max`.
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.count == 0 {
+            write!(f, "-/-/-")
+        } else {
+            write!(f, "{}/{}/{}", self.min, self.avg(), self.max)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Profiler {
+    clock: Option<fn() -> u32>,
+    started: [u32; SECTION_COUNT],
+    stats: [Stats; SECTION_COUNT],
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub const fn new() -> Self {
+        Self {
+            clock: None,
+            started: [0; SECTION_COUNT],
+            stats: [Stats::new(); SECTION_COUNT],
+        }
+    }
+
+    /// Enables profiling, `clock` returns the current time in microseconds
+    /// wrapping at 2^32.
+    pub fn set_clock(&mut self, clock: fn() -> u32) {
+        self.clock = Some(clock);
+    }
+
+    pub fn begin(&mut self, section: Section) {
+        if let Some(clock) = self.clock {
+            self.started[section as usize] = clock();
+        }
+    }
+
+    pub fn end(&mut self, section: Section) {
+        if let Some(clock) = self.clock {
+            let duration = clock().wrapping_sub(self.started[section as usize]);
+            self.stats[section as usize].add(duration);
+        }
+    }
+
+    /// Adds a duration measured by other means.
+    pub fn record(&mut self, section: Section, duration: u32) {
+        if self.clock.is_some() {
+            self.stats[section as usize].add(duration);
+        }
+    }
+
+    pub fn stats(&self, section: Section) -> &Stats {
+        &self.stats[section as usize]
+    }
+
+    /// Starts a new measurement window.
+    pub fn reset(&mut self) {
+        self.stats = [Stats::new(); SECTION_COUNT];
+    }
+}
+
+/// Prints all sections as `name min/avg/max` in microseconds.
+impl fmt::Display for Profiler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, section) in Section::ALL.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{} {}", section.name(), self.stats(*section))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::string::ToString;
+
+    std::thread_local! {
+        static NOW: Cell<u32> = const { Cell::new(0) };
+    }
+
+    fn clock() -> u32 {
+        NOW.with(|now| now.get())
+    }
+
+    fn advance(us: u32) {
+        NOW.with(|now| now.set(now.get().wrapping_add(us)));
+    }
+
+    #[test]
+    fn tracks_min_avg_max() {
+        let mut profiler = Profiler::new();
+        profiler.set_clock(clock);
+        for us in [100, 300, 200] {
+            profiler.begin(Section::Draw);
+            advance(us);
+            profiler.end(Section::Draw);
+        }
+        let stats = profiler.stats(Section::Draw);
+        assert_eq!((stats.min, stats.avg(), stats.max, stats.count()), (100, 200, 300, 3));
+        assert_eq!(profiler.stats(Section::Tick).count(), 0);
+        profiler.reset();
+        assert_eq!(profiler.stats(Section::Draw).count(), 0);
+    }
+
+    #[test]
+    fn measures_across_a_clock_wrap() {
+        let mut profiler = Profiler::new();
+        profiler.set_clock(clock);
+        NOW.with(|now| now.set(u32::MAX - 5000));
+        profiler.begin(Section::Tick);
+        advance(20_000);
+        profiler.end(Section::Tick);
+        assert_eq!(profiler.stats(Section::Tick).max, 20_000);
+    }
+
+    #[test]
+    fn prints_all_sections() {
+        let mut profiler = Profiler::new();
+        profiler.set_clock(clock);
+        profiler.begin(Section::Tick);
+        advance(42);
+        profiler.end(Section::Tick);
+        assert_eq!(profiler.to_string(), "tick 42/42/42 collide -/-/- draw -/-/- upload -/-/- wait -/-/-");
+    }
+
+    #[test]
+    fn does_nothing_without_a_clock() {
+        let mut profiler = Profiler::new();
+        profiler.begin(Section::Tick);
+        profiler.end(Section::Tick);
+        profiler.record(Section::Wait, 100);
+        assert_eq!(profiler.stats(Section::Tick).count(), 0);
+        assert_eq!(profiler.stats(Section::Wait).count(), 0);
+    }
+}
@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+/* This is based on the FastLED random8 implementation */
+
+/// Random generator with its own seed. The free functions use the game's
+/// global one, generators that must leave that alone keep their own.
+#[derive(Clone, Copy)]
+pub struct Rand16 {
+    seed: u16,
+}
+
+#[cfg(not(test))]
+static mut RAND16: Rand16 = Rand16::new(1337);
+
+// Tests run in parallel, every test thread gets its own reproducible sequence
+#[cfg(test)]
+std::thread_local! {
+    static RAND16: core::cell::Cell<Rand16> = const { core::cell::Cell::new(Rand16::new(1337)) };
+}
+
+/// Runs `f` on the global generator
+#[cfg(not(test))]
+fn global<T>(f: impl FnOnce(&mut Rand16) -> T) -> T {
+    unsafe { f(&mut *core::ptr::addr_of_mut!(RAND16)) }
+}
+
+#[cfg(test)]
+fn global<T>(f: impl FnOnce(&mut Rand16) -> T) -> T {
+    RAND16.with(|cell| {
+        let mut rand = cell.get();
+        let result = f(&mut rand);
+        cell.set(rand);
+        result
+    })
+}
+
+#[allow(dead_code)]
+pub fn random16_set_seed(seed: u16) {
+    global(|rand| rand.seed = seed);
+}
+
+pub fn random16_get_seed() -> u16 {
+    global(|rand| rand.seed)
+}
+
+#[allow(dead_code)]
+pub fn random16() -> u16 {
+    global(Rand16::random16)
+}
+
+#[allow(dead_code)]
+pub fn random8() -> u8 {
+    global(Rand16::random8)
+}
+
+#[allow(dead_code)]
+pub fn random8lim(lim: u8) -> u8 {
+    global(|rand| rand.random8lim(lim))
+}
+
+#[allow(dead_code)]
+pub fn random8minlim(min: u8, lim: u8) -> u8 {
+    global(|rand| rand.random8minlim(min, lim))
+}
+
+#[allow(dead_code)]
+pub fn random16lim(lim: u16) -> u16 {
+    global(|rand| rand.random16lim(lim))
+}
+
+#[allow(dead_code)]
+pub fn random16minlim(min: u16, lim: u16) -> u16 {
+    global(|rand| rand.random16minlim(min, lim))
+}
+
+impl Rand16 {
+    pub const fn new(seed: u16) -> Self {
+        Self { seed }
+    }
+
+    pub fn random16(&mut self) -> u16 {
+        self.seed = (self.seed.wrapping_mul(2053)).wrapping_add(13849);
+        self.seed
+    }
+
+    pub fn random8(&mut self) -> u8 {
+        let seed = self.random16();
+        ((seed & 0xFF) as u8).wrapping_add((seed >> 8) as u8)
+    }
+
+    pub fn random8lim(&mut self, lim: u8) -> u8 {
+        let r = self.random8();
+        (((r as u32 * lim as u32) >> 8) & 0xFF) as u8
+    }
+
+    pub fn random8minlim(&mut self, min: u8, lim: u8) -> u8 {
+        let delta = lim.wrapping_sub(min);
+        self.random8lim(delta).wrapping_add(min)
+    }
+
+    pub fn random16lim(&mut self, lim: u16) -> u16 {
+        let r = self.random16();
+        (((r as u32 * lim as u32) >> 16) & 0xFFFF) as u16
+    }
+
+    pub fn random16minlim(&mut self, min: u16, lim: u16) -> u16 {
+        let delta = lim.wrapping_sub(min);
+        self.random16lim(delta).wrapping_add(min)
+    }
+}
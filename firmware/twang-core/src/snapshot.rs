@@ -0,0 +1,297 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Compact binary snapshots of the whole game state.
+//!
+//! A snapshot holds everything `Twang::cycle` depends on, the state machine,
+//! all entity pools and the random seed, so a game can be rewound, or dumped
+//! by the firmware and continued in the simulator. The LED colors are not
+//! saved, the next cycle draws them again.
+//!
+//! The fields are written in a fixed order, little endian, dead pool entries
+//! as a single byte. A magic and a version go in front, an FNV-1a hash of all
+//! that comes before at the end.
+
+use core::fmt;
+
+pub const SNAPSHOT_VERSION: u8 = 1;
+const MAGIC: [u8; 3] = *b"TWS";
+pub(crate) const FNV_OFFSET: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SnapshotError {
+    Truncated,      // The data ended early
+    Magic,          // Not a snapshot
+    Version(u8),    // Written by a different version of the game
+    Checksum,       // Damaged on the way
+    Invalid,        // A field is out of range
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Truncated => write!(f, "snapshot truncated"),
+            SnapshotError::Magic => write!(f, "not a snapshot"),
+            SnapshotError::Version(version) => write!(f, "snapshot version {}, expected {}", version, SNAPSHOT_VERSION),
+            SnapshotError::Checksum => write!(f, "snapshot checksum mismatch"),
+            SnapshotError::Invalid => write!(f, "snapshot field out of range"),
+        }
+    }
+}
+
+pub(crate) fn fnv1a(hash: u32, byte: u8) -> u32 {
+    (hash ^ byte as u32).wrapping_mul(FNV_PRIME)
+}
+
+/// Streams the snapshot bytes out, so the firmware needs no buffer.
+pub struct Writer<'a> {
+    out: &'a mut dyn FnMut(u8),
+    hash: u32,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(out: &'a mut dyn FnMut(u8)) -> Self {
+        let mut writer = Self { out, hash: FNV_OFFSET };
+        for byte in MAGIC {
+            writer.u8(byte);
+        }
+        writer.u8(SNAPSHOT_VERSION);
+        writer
+    }
+
+    /// Ends the snapshot with the checksum.
+    pub fn finish(mut self) {
+        let hash = self.hash;
+        self.u32(hash);
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.hash = fnv1a(self.hash, value);
+        (self.out)(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        for byte in value.to_le_bytes() {
+            self.u8(byte);
+        }
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        for byte in value.to_le_bytes() {
+            self.u8(byte);
+        }
+    }
+
+    pub fn i32(&mut self, value: i32) {
+        self.u32(value as u32);
+    }
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Checks the framing of `data` and starts reading after the header.
+    pub fn new(data: &'a [u8]) -> Result<Self, SnapshotError> {
+        if data.len() < MAGIC.len() + 5 {
+            return Err(SnapshotError::Truncated);
+        }
+        if data[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::Magic);
+        }
+        let version = data[MAGIC.len()];
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let (body, hash) = data.split_at(data.len() - 4);
+        let hash = u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]);
+        if body.iter().fold(FNV_OFFSET, |h, &b| fnv1a(h, b)) != hash {
+            return Err(SnapshotError::Checksum);
+        }
+        Ok(Self { data: body, pos: MAGIC.len() + 1 })
+    }
+
+    /// Fails unless all fields were read.
+    pub fn finish(self) -> Result<(), SnapshotError> {
+        if self.pos == self.data.len() {
+            Ok(())
+        } else {
+            Err(SnapshotError::Invalid)
+        }
+    }
+
+    pub fn u8(&mut self) -> Result<u8, SnapshotError> {
+        let value = *self.data.get(self.pos).ok_or(SnapshotError::Truncated)?;
+        self.pos += 1;
+        Ok(value)
+    }
+
+    pub fn bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Invalid),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, SnapshotError> {
+        Ok(u16::from_le_bytes([self.u8()?, self.u8()?]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes([self.u8()?, self.u8()?, self.u8()?, self.u8()?]))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, SnapshotError> {
+        Ok(self.u32()? as i32)
+    }
+}
+
+/// Game parts that go into a snapshot.
+pub trait Save {
+    fn save(&self, w: &mut Writer);
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError>;
+}
+
+impl<T: Save, const N: usize> Save for [T; N] {
+    fn save(&self, w: &mut Writer) {
+        for item in self {
+            item.save(w);
+        }
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        for item in self {
+            item.load(r)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, Status, Twang};
+
+    /// Plays `frames` frames walking right and firing now and then, returns
+    /// the LEDs of the last frame.
+    fn play(twang: &mut Twang, time: &mut u32, frames: u32) -> Vec<[u8; 3]> {
+        for frame in 0..frames {
+            twang.cycle(10, frame % 20 == 0, *time);
+            *time += 16;
+        }
+        (0..twang.get_led_len()).map(|i| twang.get_led(i)).collect()
+    }
+
+    fn framed(fill: &mut dyn FnMut(&mut Writer)) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut out = |b| data.push(b);
+        let mut w = Writer::new(&mut out);
+        fill(&mut w);
+        w.finish();
+        data
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = framed(&mut |w| {
+            w.i32(-5);
+            w.bool(true);
+            w.u16(0xBEEF);
+        });
+        let mut r = Reader::new(&data).unwrap();
+        assert_eq!(r.i32(), Ok(-5));
+        assert_eq!(r.bool(), Ok(true));
+        assert_eq!(r.u16(), Ok(0xBEEF));
+        assert_eq!(r.u8(), Err(SnapshotError::Truncated));
+        r.finish().unwrap();
+    }
+
+    #[test]
+    fn continues_the_same_game() {
+        for (config, level) in [(Config::default(), 3), (Config { endless: true, ..Config::default() }, 7)] {
+            let mut twang = Twang::new(config);
+            let mut time = 1000;
+            twang.start_level(level, time);
+            play(&mut twang, &mut time, 50);
+
+            let mut data = Vec::new();
+            twang.save_snapshot(time, &mut |b| data.push(b));
+            let saved = time;
+            let expected = play(&mut twang, &mut time, 100);
+
+            let mut other = Twang::new(Config::default());
+            let mut time = other.load_snapshot(&data).unwrap();
+            assert_eq!(time, saved);
+            assert_eq!(play(&mut other, &mut time, 100), expected);
+            assert_eq!(other.status(), twang.status());
+            assert_eq!(other.player_position(), twang.player_position());
+        }
+    }
+
+    #[test]
+    fn broken_snapshot_leaves_the_game_alone() {
+        let mut twang = Twang::new(Config::default());
+        twang.start_level(2, 0);
+        let mut data = Vec::new();
+        twang.save_snapshot(0, &mut |b| data.push(b));
+        let len = data.len();
+        data.truncate(len - 10);
+        let mut other = Twang::new(Config::default());
+        assert!(other.load_snapshot(&data).is_err());
+        assert_eq!(other.status(), Status::Screensaver);
+    }
+
+    #[test]
+    fn rejects_impossible_strip_lengths() {
+        let data = framed(&mut |w| {
+            w.u32(0);
+            w.u16(1337);
+            w.u32(0);
+            w.bool(false);
+            w.u16(0);
+        });
+        let mut twang = Twang::new(Config::default());
+        assert_eq!(twang.load_snapshot(&data).err(), Some(SnapshotError::Invalid));
+    }
+
+    #[test]
+    fn rejects_damage() {
+        let mut data = framed(&mut |w| w.u32(1234));
+        assert!(Reader::new(&data[..6]).is_err());
+        data[5] ^= 1;
+        assert_eq!(Reader::new(&data).err(), Some(SnapshotError::Checksum));
+        data[3] = 99;
+        assert_eq!(Reader::new(&data).err(), Some(SnapshotError::Version(99)));
+        data[0] = b'X';
+        assert_eq!(Reader::new(&data).err(), Some(SnapshotError::Magic));
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::{led_string::LEDString,enemy::{Enemy, EnemyKind}};
+use super::inspect::Entity;
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Spawner {
+    position: i32,      // Spawner position
+    rate: u32,          // Spawn rate in ms
+    speed: i32,         // Eneemy speed and direction when exiting the spawner
+    last_spawned: u32,  // Time of last spawn
+    activate: u32,      // Time of activation
+    kind: EnemyKind,    // Kind of enemies spawned
+    pub alive: bool,        // Is this spawner alive
+}
+
+impl Spawner {
+    pub fn new() -> Self {
+        Self {
+            position: 500,
+            rate: 0,
+            speed: 0,
+            last_spawned: 0,
+            activate: 0,
+            kind: EnemyKind::Basic,
+            alive: false,
+        }
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString) {
+        if self.alive {
+            let pos = led_string.vtor(self.position);
+            led_string[pos].set_rgb([64, 0, 64]);
+        }
+    }
+
+    pub fn tick(&mut self, time: u32, enemies: &mut [Enemy]) {
+        if !self.alive || self.activate >= time {
+            return;
+        }
+        if self.last_spawned + self.rate < time || self.last_spawned == 0 {
+            for enemy in enemies.iter_mut() {
+                    if enemy.alive { continue }
+                else {
+                    enemy.spawn(self.position, self.speed, 0, self.kind);
+                    self.last_spawned = time;
+                    return;
+                }
+            }
+           }
+    }
+
+    pub fn move_to(&mut self, position: i32) {
+        self.position = position;
+    }
+
+    pub fn inspect(&self, time: u32) -> Entity {
+        // Spawns on the first tick past the activation and the rate
+        let due = if self.last_spawned == 0 {
+            self.activate + 1
+        } else {
+            self.activate.max(self.last_spawned + self.rate) + 1
+        };
+        Entity::Spawner{position: self.position, kind: self.kind, next_spawn: due.saturating_sub(time)}
+    }
+
+    pub fn reset(&mut self) {
+        self.alive = false;
+    }
+
+    pub fn spawn(&mut self, time: u32, position: i32, rate: u32, speed: i32, activate: u32, kind: EnemyKind) {
+        self.position = position;
+        self.kind = kind;
+        self.rate = rate;
+        self.speed = speed;
+        self.last_spawned = 0;
+        self.activate = time + activate;
+        self.alive = true;
+    }
+}
+
+impl Save for Spawner {
+    fn save(&self, w: &mut Writer) {
+        w.bool(self.alive);
+        if !self.alive {
+            return;
+        }
+        w.i32(self.position);
+        w.u32(self.rate);
+        w.i32(self.speed);
+        w.u32(self.last_spawned);
+        w.u32(self.activate);
+        self.kind.save(w);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        *self = Self::new();
+        self.alive = r.bool()?;
+        if !self.alive {
+            return Ok(());
+        }
+        self.position = r.i32()?;
+        self.rate = r.u32()?;
+        self.speed = r.i32()?;
+        self.last_spawned = r.u32()?;
+        self.activate = r.u32()?;
+        self.kind.load(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawns_at_its_rate() {
+        let mut enemies = [Enemy::new(); 3];
+        let mut spawner = Spawner::new();
+        spawner.spawn(1000, 900, 500, -2, 100, EnemyKind::Chaser);
+        spawner.tick(1100, &mut enemies);
+        assert!(!enemies[0].alive);
+        spawner.tick(1101, &mut enemies);
+        assert!(enemies[0].alive);
+        assert_eq!(enemies[0].position, 900);
+        assert!(enemies[0].kind == EnemyKind::Chaser);
+        spawner.tick(1601, &mut enemies);
+        assert!(!enemies[1].alive);
+        spawner.tick(1602, &mut enemies);
+        assert!(enemies[1].alive);
+    }
+
+    #[test]
+    fn counts_down_to_the_next_spawn() {
+        let mut enemies = [Enemy::new(); 3];
+        let mut spawner = Spawner::new();
+        spawner.spawn(1000, 900, 500, -2, 100, EnemyKind::Chaser);
+        assert_eq!(spawner.inspect(1000), Entity::Spawner{position: 900, kind: EnemyKind::Chaser, next_spawn: 101});
+        spawner.tick(1101, &mut enemies);
+        assert_eq!(spawner.inspect(1101), Entity::Spawner{position: 900, kind: EnemyKind::Chaser, next_spawn: 501});
+        assert_eq!(spawner.inspect(1700), Entity::Spawner{position: 900, kind: EnemyKind::Chaser, next_spawn: 0});
+    }
+
+    #[test]
+    fn stops_when_the_pool_is_full() {
+        let mut enemies = [Enemy::new(); 1];
+        let mut spawner = Spawner::new();
+        spawner.spawn(0, 900, 10, -2, 0, EnemyKind::Basic);
+        spawner.tick(1, &mut enemies);
+        enemies[0].position = 100;
+        spawner.tick(100, &mut enemies);
+        assert_eq!(enemies[0].position, 100);
+    }
+}
@@ -0,0 +1,164 @@
+/*
+ * Copyright (c) 2020-2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use core::ops::*;
+use core::cmp::PartialOrd;
+
+/// Maps `i` from the `imin..imax` range onto the `omin..omax` range. Either
+/// range may be reversed. Works with unsigned types as long as `i` is inside
+/// the input range.
+pub fn range_map<T>(i: T, imin: T, imax: T, omin: T, omax: T) -> T
+where
+    T: Add<Output = T> + Sub<Output = T> +
+        Mul<Output = T> + Div<Output = T> +
+        PartialOrd + Copy
+{
+    // Measure the distance from imin towards imax, so unsigned types never
+    // go below zero on a reversed input range
+    let (ispan, ioffset) = if imax < imin {
+        (imin - imax, imin - i)
+    } else {
+        (imax - imin, i - imin)
+    };
+    if omax < omin {
+        omin - ((ioffset * (omin - omax)) / ispan)
+    } else {
+        ((ioffset * (omax - omin)) / ispan) + omin
+    }
+}
+
+pub fn constrain<T>(i: T, min: T, max: T) -> T
+where
+    T: PartialOrd
+{
+    if i < min {
+        min
+    } else if i > max {
+        max
+    } else {
+        i
+    }
+}
+
+const SIN_LUT_SIZE: usize = 64;
+const SIN_LUT: [u8; SIN_LUT_SIZE] = [
+    0,0,0,0,1,1,1,2,
+    2,3,4,5,5,6,7,9,
+    10,11,12,14,15,17,18,20,
+    21,23,25,27,29,31,33,35,
+    37,40,42,44,47,49,52,54,
+    57,59,62,65,67,70,73,76,
+    79,82,85,88,90,93,97,100,
+    103,106,109,112,115,118,121,124
+];
+
+pub fn sinu8(n: u8) -> u8 {
+    if n < SIN_LUT_SIZE as u8 { SIN_LUT[n as usize] }
+    else if n < (SIN_LUT_SIZE * 2) as u8 { 255 - SIN_LUT[SIN_LUT_SIZE * 2 - n as usize - 1] }
+    else if n < (SIN_LUT_SIZE * 3) as u8 { 255 - SIN_LUT[n as usize - (SIN_LUT_SIZE * 2)] }
+    else { SIN_LUT[(SIN_LUT_SIZE * 4 - 1) - n as usize] }
+}
+
+pub fn sini8(n: i8) -> i8 {
+    let nu = ((n as i32) + 128) as u8;
+    let su8 = sinu8(nu);
+    ((su8 as i32) - 128) as i8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_map_forward() {
+        assert_eq!(range_map(0, 0, 10, 0, 100), 0);
+        assert_eq!(range_map(5, 0, 10, 0, 100), 50);
+        assert_eq!(range_map(10, 0, 10, 0, 100), 100);
+        assert_eq!(range_map(15, 10, 20, -50, 50), 0);
+    }
+
+    #[test]
+    fn range_map_reversed_output() {
+        assert_eq!(range_map(0, 0, 10, 100, 0), 100);
+        assert_eq!(range_map(5, 0, 10, 100, 0), 50);
+        assert_eq!(range_map(10, 0, 10, 100, 0), 0);
+        assert_eq!(range_map(250u32, 0, 1000, 255, 0), 192);
+    }
+
+    #[test]
+    fn range_map_reversed_input() {
+        assert_eq!(range_map(10, 10, 0, 0, 100), 0);
+        assert_eq!(range_map(5, 10, 0, 0, 100), 50);
+        assert_eq!(range_map(0, 10, 0, 0, 100), 100);
+        assert_eq!(range_map(0, 10, 0, 100, 0), 0);
+    }
+
+    #[test]
+    fn range_map_unsigned_reversed_input() {
+        // Used to underflow computing i - imin
+        assert_eq!(range_map(5u32, 10, 0, 0, 100), 50);
+        assert_eq!(range_map(5u8, 10, 0, 20, 0), 10);
+        assert_eq!(range_map(0u32, 1500, 0, 0, 143), 143);
+    }
+
+    #[test]
+    fn range_map_extrapolates() {
+        assert_eq!(range_map(20, 0, 10, 0, 100), 200);
+        assert_eq!(range_map(-10, 0, 10, 100, 0), 200);
+    }
+
+    #[test]
+    fn constrain_clamps() {
+        assert_eq!(constrain(-5, 0, 10), 0);
+        assert_eq!(constrain(5, 0, 10), 5);
+        assert_eq!(constrain(15, 0, 10), 10);
+    }
+
+    #[test]
+    fn sinu8_shape() {
+        assert_eq!(sinu8(0), 0);
+        assert_eq!(sinu8(255), 0);
+        // Peak in the middle of the period
+        let peak = (0..=255u8).map(sinu8).max().unwrap();
+        assert!(sinu8(127) == peak || sinu8(128) == peak);
+        // Mirrored around the middle
+        for n in 0..128u8 {
+            assert_eq!(sinu8(n), sinu8(255 - n), "n = {}", n);
+        }
+        // Rising in the first half
+        for n in 1..128u8 {
+            assert!(sinu8(n) >= sinu8(n - 1), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn sini8_shape() {
+        assert_eq!(sini8(-128), -128);
+        assert_eq!(sini8(127), -128);
+        for n in -127..=127i8 {
+            assert!(sini8(n) >= -128);
+        }
+        assert!(sini8(0) > 100);
+    }
+}
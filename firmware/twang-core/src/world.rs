@@ -0,0 +1,569 @@
+/*
+ * Copyright (c) 2020-2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use core::fmt::{self, Write};
+
+use super::conveyor::Conveyor;
+use super::input::LR_MAX;
+use super::portal::Portal;
+use super::led_string::{LEDString, LED_STRING_VLENGTH};
+use super::enemy::{Enemy, EnemyKind};
+use super::spawner::Spawner;
+use super::lava::Lava;
+use super::lava_flow::LavaFlow;
+use super::player::Player;
+use super::particle::Particle;
+use super::boss::{Boss, BossKind};
+use super::inspect::{Entity, Hitbox, HitboxKind};
+use super::snapshot::{Reader, Save, SnapshotError, Writer};
+
+pub const ENEMY_POOL_COUNT: usize = 10;
+pub const SPAWNER_POOL_COUNT: usize = 2;
+pub const LAVA_POOL_COUNT: usize = 4;
+const LAVA_FLOW_POOL_COUNT: usize = 2;
+pub const CONVEYOR_POOL_COUNT: usize = 2;
+const PORTAL_POOL_COUNT: usize = 2;
+const PARTICLE_POOL_COUNT: usize = 40;
+const SPLIT_DISTANCE: i32 = 100;
+const SPLIT_SPEED: i32 = 2;
+pub const MAX_DIFFICULTY: u32 = 200;   // Later loops don't get any harder
+const MIN_PERIOD: u32 = 500;            // Shortest spawn rate or lava off time difficulty scales to
+const MAX_CONVEYOR_SPEED: i32 = LR_MAX - 2; // Fastest conveyor difficulty scales to, the player can still walk against it
+
+/// Speeds things up by the difficulty factor, rounding away from zero
+pub fn scale_speed(speed: i32, difficulty: u32) -> i32 {
+    (speed * difficulty as i32 + speed.signum() * 50) / 100
+}
+
+/// Speeds conveyors up by the difficulty factor, never past
+/// MAX_CONVEYOR_SPEED unless they already are that fast
+pub fn scale_conveyor_speed(speed: i32, difficulty: u32) -> i32 {
+    let max = MAX_CONVEYOR_SPEED.max(speed.abs());
+    scale_speed(speed, difficulty).clamp(-max, max)
+}
+
+/// Shortens time periods by the difficulty factor, periods already shorter
+/// than MIN_PERIOD stay as they are
+pub fn scale_period(period: u32, difficulty: u32) -> u32 {
+    (period * 100 / difficulty).max(period.min(MIN_PERIOD))
+}
+
+#[derive(Clone)]
+pub struct World {
+    player: Player,
+    enemies: [Enemy; ENEMY_POOL_COUNT],
+    spawners: [Spawner; SPAWNER_POOL_COUNT],
+    lavas: [Lava; LAVA_POOL_COUNT],
+    lava_flows: [LavaFlow; LAVA_FLOW_POOL_COUNT],
+    conveyors: [Conveyor; CONVEYOR_POOL_COUNT],
+    portals: [Portal; PORTAL_POOL_COUNT],
+    particles: [Particle; PARTICLE_POOL_COUNT],
+    boss: Boss,
+    difficulty: u32,    // Difficulty in percent, 100 is the normal game
+}
+
+impl World {
+    pub fn new() -> World {
+        World {
+            player: Player::new(1),
+            enemies: [Enemy::new(); ENEMY_POOL_COUNT],
+            spawners: [Spawner::new(); SPAWNER_POOL_COUNT],
+            lavas: [Lava::new(); LAVA_POOL_COUNT],
+            lava_flows: [LavaFlow::new(); LAVA_FLOW_POOL_COUNT],
+            conveyors: [Conveyor::new(); CONVEYOR_POOL_COUNT],
+            portals: [Portal::new(); PORTAL_POOL_COUNT],
+            particles: [Particle::new(); PARTICLE_POOL_COUNT],
+            boss: Boss::new(),
+            difficulty: 100,
+        }
+    }
+
+    pub fn tick(&mut self, led_string: &LEDString, time: u32) {
+        self.player.tick(led_string, time);
+        for i in 0..self.enemies.len() {
+            self.enemies[i].tick(led_string, time, self.player.position);
+        }
+        for i in 0..self.spawners.len() {
+            self.spawners[i].tick(time, &mut self.enemies)
+        }
+        for i in 0..self.lavas.len() {
+            self.lavas[i].tick(time);
+        }
+        for i in 0..self.lava_flows.len() {
+            self.lava_flows[i].tick(time);
+        }
+        self.boss.tick(time, self.player.position, &mut self.spawners, &mut self.enemies);
+    }
+
+    pub fn collide(&mut self, time: u32) {
+        // Warp first so everything else sees the final positions
+        for i in 0..self.portals.len() {
+            if self.player.collide_portal(&self.portals[i]) {
+                // The player may have ended up on the other side of enemies
+                for j in 0..self.enemies.len() {
+                    self.enemies[j].player_side = 0;
+                }
+            }
+            for j in 0..self.enemies.len() {
+                self.enemies[j].collide_portal(&self.portals[i]);
+            }
+        }
+        for i in 0..self.enemies.len() {
+            self.player.collide_enemy(&self.enemies[i]);
+            if self.enemies[i].collide_player(&self.player) &&
+                self.enemies[i].kind == EnemyKind::Splitter {
+                // Split into two enemies out of reach behind the one we killed, far
+                // enough to get another attack in. The running attack can't kill them,
+                // the ones that would end up past the end of the strip are lost.
+                let side = self.enemies[i].player_side;
+                let position = self.player.position + side * (self.player.attack_cur_width / 2 + SPLIT_DISTANCE);
+                let speed = scale_speed(-side * SPLIT_SPEED, self.difficulty);
+                for offset in [0, side * SPLIT_DISTANCE] {
+                    if !(0..LED_STRING_VLENGTH as i32).contains(&(position + offset)) {
+                        continue;
+                    }
+                    for j in 0..self.enemies.len() {
+                        if !self.enemies[j].alive {
+                            self.enemies[j].spawn(position + offset, speed, 0, EnemyKind::Basic);
+                            self.enemies[j].spare(self.player.attacking_millis);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        for i in 0..self.lavas.len() {
+            self.player.collide_lava(&self.lavas[i]);
+            for j in 0..self.enemies.len() {
+                self.enemies[j].collide_lava(&self.lavas[i]);
+            }
+        }
+        for i in 0..self.lava_flows.len() {
+            self.lava_flows[i].collide_player(&self.player, time);
+            self.player.collide_lava_flow(&self.lava_flows[i]);
+            for j in 0..self.enemies.len() {
+                self.enemies[j].collide_lava_flow(&self.lava_flows[i]);
+            }
+        }
+        for i in 0..self.conveyors.len() {
+            self.player.collide_conveyor(&self.conveyors[i]);
+        }
+        self.boss.collide_player(&self.player, &mut self.spawners, time);
+        self.player.collide_boss(&self.boss);
+    }
+
+    pub fn draw(&self, led_string: &mut LEDString, time: u32) {
+        for i in 0..self.spawners.len() {
+            self.spawners[i].draw(led_string);
+        }
+
+        for i in 0..self.lavas.len() {
+            self.lavas[i].draw(led_string);
+        }
+
+        for i in 0..self.lava_flows.len() {
+            self.lava_flows[i].draw(led_string);
+        }
+
+        for i in 0..self.conveyors.len() {
+            self.conveyors[i].draw(led_string, time);
+        }
+
+        for i in 0..self.portals.len() {
+            self.portals[i].draw(led_string, time);
+        }
+
+        self.boss.draw(led_string, time);
+
+        // Enemies walk on conveyors and other stuff
+        for i in 0..self.enemies.len() {
+            self.enemies[i].draw(led_string, time, self.player.position);
+        }
+
+        // Player walks on everything
+        self.player.draw(led_string, time);
+
+        // Draw exit
+        // You can't escape the boss so there is no exit ;)
+        if !self.boss.alive {
+            let exit = led_string.len() - 1;
+            led_string[exit].set_rgb([0, 0, 255]);
+        }
+    }
+
+    pub fn player_set_speed(&mut self, val: i32) {
+        self.player.speed = val;
+    }
+
+    pub fn player_attack(&mut self, fire: bool, time: u32) {
+        self.player.attack(fire, time);
+    }
+
+    pub fn player_set_attack_width(&mut self, width: i32) {
+        self.player.attack_width = width;
+    }
+
+    pub fn player_set_attack_cooldown(&mut self, cooldown: u32) {
+        self.player.attack_cooldown = cooldown;
+    }
+
+    pub fn player_set_attack_charge(&mut self, duration: u32, width: i32) {
+        self.player.attack_charge_duration = duration;
+        self.player.attack_charge_width = width;
+    }
+
+    pub fn player_alive(&self) -> bool {
+        self.player.alive
+    }
+
+    pub fn player_position(&self) -> i32 {
+        self.player.position
+    }
+
+    pub fn player_lives(&self) -> u8 {
+        self.player.lives
+    }
+
+    pub fn player_set_lives(&mut self, lives: u8) {
+        self.player.lives = lives;
+    }
+
+    pub fn player_god(&self) -> bool {
+        self.player.god
+    }
+
+    pub fn player_set_god(&mut self, god: bool) {
+        self.player.god = god;
+    }
+
+    pub fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+
+    /// Sets the difficulty in percent, capped at MAX_DIFFICULTY
+    pub fn set_difficulty(&mut self, difficulty: u32) {
+        self.difficulty = difficulty.clamp(100, MAX_DIFFICULTY);
+    }
+
+    pub fn enemies_alive(&self) -> u32 {
+        self.enemies.iter().filter(|e| e.alive).count() as u32
+    }
+
+    pub fn boss_hits_left(&self) -> u32 {
+        self.boss.hits_left()
+    }
+
+    pub fn boss_killed(&self) -> bool {
+        !self.boss.alive && self.boss.defeated
+    }
+
+    /// Writes the state of the player and all live entities, one per line.
+    pub fn dump(&self, out: &mut dyn Write) -> fmt::Result {
+        writeln!(out, "difficulty {}%", self.difficulty)?;
+        writeln!(out, "{:?}", self.player)?;
+        for enemy in self.enemies.iter().filter(|e| e.alive) {
+            writeln!(out, "{:?}", enemy)?;
+        }
+        for spawner in self.spawners.iter().filter(|s| s.alive) {
+            writeln!(out, "{:?}", spawner)?;
+        }
+        for lava in self.lavas.iter().filter(|l| l.alive) {
+            writeln!(out, "{:?}", lava)?;
+        }
+        for flow in self.lava_flows.iter().filter(|f| f.alive) {
+            writeln!(out, "{:?}", flow)?;
+        }
+        for conveyor in self.conveyors.iter().filter(|c| c.alive) {
+            writeln!(out, "{:?}", conveyor)?;
+        }
+        for portal in self.portals.iter().filter(|p| p.alive) {
+            writeln!(out, "{:?}", portal)?;
+        }
+        if self.boss.alive {
+            writeln!(out, "Boss {{ position: {}, hits_left: {}, lava_on: {} }}",
+                self.boss.position, self.boss.hits_left(), self.boss.lava_on)?;
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the hitbox of the player and every live entity.
+    pub fn hitboxes(&self, f: &mut dyn FnMut(Hitbox)) {
+        let player = &self.player;
+        if player.alive {
+            f(Hitbox::new(HitboxKind::Player, player.position, player.position, false));
+            if player.attacking {
+                let reach = player.attack_cur_width / 2;
+                f(Hitbox::new(HitboxKind::Attack, player.position - reach, player.position + reach, false));
+            }
+        }
+        for enemy in self.enemies.iter().filter(|e| e.alive) {
+            f(Hitbox::new(HitboxKind::Enemy, enemy.position, enemy.position, true));
+        }
+        for lava in self.lavas.iter().filter(|l| l.alive) {
+            f(Hitbox::new(HitboxKind::Lava, lava.pos_start, lava.pos_end - 1, lava.state));
+        }
+        for flow in self.lava_flows.iter().filter(|f| f.alive) {
+            f(Hitbox::new(HitboxKind::LavaFlow, flow.pos_start, flow.pos_end, flow.hot));
+        }
+        for conveyor in self.conveyors.iter().filter(|c| c.alive) {
+            f(Hitbox::new(HitboxKind::Conveyor, conveyor.pos_start, conveyor.pos_end - 1, false));
+        }
+        for portal in self.portals.iter().filter(|p| p.alive) {
+            f(Hitbox::new(HitboxKind::Portal, portal.pos_a, portal.pos_a, false));
+            f(Hitbox::new(HitboxKind::Portal, portal.pos_b, portal.pos_b, false));
+        }
+        let boss = &self.boss;
+        if boss.alive {
+            // The player dies strictly between start and stop
+            f(Hitbox::new(HitboxKind::Boss, boss.start + 1, boss.stop - 1, true));
+            if let Some(reach) = boss.lava_reach() {
+                f(Hitbox::new(HitboxKind::BossLava, boss.start - reach, boss.start, true));
+                f(Hitbox::new(HitboxKind::BossLava, boss.stop, boss.stop + reach, true));
+            }
+        }
+    }
+
+    /// Calls `f` with the state of the player and every live entity.
+    pub fn inspect(&self, time: u32, f: &mut dyn FnMut(Entity)) {
+        if self.player.alive {
+            f(self.player.inspect(time));
+        }
+        for enemy in self.enemies.iter().filter(|e| e.alive) {
+            f(enemy.inspect());
+        }
+        for spawner in self.spawners.iter().filter(|s| s.alive) {
+            f(spawner.inspect(time));
+        }
+        for lava in self.lavas.iter().filter(|l| l.alive) {
+            f(lava.inspect(time));
+        }
+        for flow in self.lava_flows.iter().filter(|f| f.alive) {
+            f(flow.inspect());
+        }
+        for conveyor in self.conveyors.iter().filter(|c| c.alive) {
+            f(conveyor.inspect());
+        }
+        for portal in self.portals.iter().filter(|p| p.alive) {
+            f(portal.inspect());
+        }
+        if self.boss.alive {
+            f(self.boss.inspect());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.player.reset();
+        for i in 0..self.enemies.len() {
+            self.enemies[i].reset();
+        }
+        for i in 0..self.spawners.len() {
+            self.spawners[i].reset();
+        }
+        for i in 0..self.lavas.len() {
+            self.lavas[i].reset();
+        }
+        for i in 0..self.lava_flows.len() {
+            self.lava_flows[i].reset();
+        }
+        for i in 0..self.conveyors.len() {
+            self.conveyors[i].reset();
+        }
+        for i in 0..self.portals.len() {
+            self.portals[i].reset();
+        }
+        self.boss.reset();
+    }
+
+    pub fn spawn_player(&mut self, position: i32) {
+        self.player.spawn(position);
+    }
+
+    pub fn spawn_enemy(&mut self, position: i32, speed: i32, wobble: i32, kind: EnemyKind) {
+        for i in 0..self.enemies.len() {
+            if self.enemies[i].alive { continue }
+            else {
+                let speed = scale_speed(speed, self.difficulty);
+                self.enemies[i].spawn(position, speed, wobble, kind);
+                return;
+            }
+        }
+    }
+
+    pub fn spawn_spawner(&mut self, time: u32, position: i32, rate: u32, speed: i32, activate: u32, kind: EnemyKind) {
+        for i in 0..self.spawners.len() {
+            if self.spawners[i].alive { continue }
+            else {
+                let rate = scale_period(rate, self.difficulty);
+                let speed = scale_speed(speed, self.difficulty);
+                self.spawners[i].spawn(time, position, rate, speed, activate, kind);
+                return;
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_lava(&mut self, time: u32, pos_start: i32, pos_end: i32, ontime: u32, offtime: u32, offset: u32, state: bool) {
+        for i in 0..self.lavas.len() {
+            if self.lavas[i].alive { continue }
+            else {
+                // Lava stays on longer and rests shorter
+                let ontime = ontime * self.difficulty / 100;
+                let offtime = scale_period(offtime, self.difficulty);
+                self.lavas[i].spawn(time, pos_start, pos_end, ontime, offtime, offset, state);
+                return;
+            }
+        }
+    }
+
+    pub fn spawn_lava_flow(&mut self, position: i32, width: i32, speed: i32, bound_min: i32, bound_max: i32) {
+        for i in 0..self.lava_flows.len() {
+            if self.lava_flows[i].alive { continue }
+            else {
+                self.lava_flows[i].spawn(position, width, speed, bound_min, bound_max);
+                return;
+            }
+        }
+    }
+
+    pub fn spawn_conveyor(&mut self, pos_start: i32, pos_end: i32, speed: i32) {
+        for i in 0..self.conveyors.len() {
+            if self.conveyors[i].alive { continue }
+            else {
+                // Conveyors push harder
+                let speed = scale_conveyor_speed(speed, self.difficulty);
+                self.conveyors[i].spawn(pos_start, pos_end, speed);
+                return;
+            }
+        }
+    }
+
+    pub fn spawn_portal(&mut self, pos_a: i32, pos_b: i32) {
+        for i in 0..self.portals.len() {
+            if self.portals[i].alive { continue }
+            else {
+                self.portals[i].spawn(pos_a, pos_b);
+                return;
+            }
+        }
+    }
+
+    pub fn spawn_particles(&mut self, position: i32) {
+        for i in 0..self.particles.len() {
+            self.particles[i].spawn(position);
+        }
+    }
+
+    pub fn spawn_boss(&mut self, time: u32, kind: BossKind) {
+        self.boss.spawn(time, kind, self.difficulty, &mut self.spawners);
+    }
+
+    // Returns true if still active
+    pub fn cycle_particles(&mut self, led_string: &mut LEDString, gravity: bool, bend: i32) -> bool {
+        let mut active = false;
+        for i in 0..self.particles.len() {
+            self.particles[i].tick(gravity, bend);
+            if self.particles[i].draw(led_string) {
+                active = true;
+            }
+        }
+        active
+    }
+
+    /// The exit stays shut while a boss is still alive
+    pub fn exit_n(&self) -> bool {
+        self.player.position == 999 && !self.boss.alive
+    }
+
+    #[cfg(test)]
+    pub fn hit_boss(&mut self, time: u32) {
+        self.boss.hit(&mut self.spawners, time);
+    }
+}
+
+impl Save for World {
+    fn save(&self, w: &mut Writer) {
+        self.player.save(w);
+        self.enemies.save(w);
+        self.spawners.save(w);
+        self.lavas.save(w);
+        self.lava_flows.save(w);
+        self.conveyors.save(w);
+        self.portals.save(w);
+        self.particles.save(w);
+        self.boss.save(w);
+        w.u32(self.difficulty);
+    }
+
+    fn load(&mut self, r: &mut Reader) -> Result<(), SnapshotError> {
+        self.player.load(r)?;
+        self.enemies.load(r)?;
+        self.spawners.load(r)?;
+        self.lavas.load(r)?;
+        self.lava_flows.load(r)?;
+        self.conveyors.load(r)?;
+        self.portals.load(r)?;
+        self.particles.load(r)?;
+        self.boss.load(r)?;
+        self.difficulty = r.u32()?;
+        // Scaling divides by it, set_difficulty keeps it in range
+        if !(100..=MAX_DIFFICULTY).contains(&self.difficulty) {
+            return Err(SnapshotError::Invalid);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_at(position: i32, side: i32) -> World {
+        let mut world = World::new();
+        world.spawn_player(position);
+        world.spawn_enemy(position + side * 20, 0, 0, EnemyKind::Splitter);
+        world.player_attack(true, 1000);
+        // The first contact only picks the side
+        world.collide(1000);
+        world.collide(1016);
+        world
+    }
+
+    fn alive(world: &World) -> Vec<i32> {
+        world.enemies.iter().filter(|e| e.alive).map(|e| e.position).collect()
+    }
+
+    #[test]
+    fn splits_behind_the_killed_enemy() {
+        let world = split_at(500, 1);
+        let behind = 500 + world.player.attack_cur_width / 2 + SPLIT_DISTANCE;
+        assert_eq!(alive(&world), [behind, behind + SPLIT_DISTANCE]);
+    }
+
+    #[test]
+    fn splits_stay_on_the_strip() {
+        assert!(alive(&split_at(950, 1)).is_empty());
+        let world = split_at(150, -1);
+        assert_eq!(alive(&world), [150 - world.player.attack_cur_width / 2 - SPLIT_DISTANCE]);
+    }
+}
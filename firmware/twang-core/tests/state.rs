@@ -0,0 +1,240 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use twang_core::{Config, Status, Twang, LED_STRING_MAX_LENGTH, LED_STRING_VLENGTH, LEVEL_COUNT};
+use twang_core::inspect::{Entity, Hitbox, HitboxKind};
+use twang_core::log::{Event, RingLogger};
+
+const FRAME_TIME: u32 = 16;
+
+struct Game {
+    twang: Twang,
+    time: u32,
+}
+
+impl Game {
+    fn new(config: Config) -> Self {
+        Self { twang: Twang::new(config), time: 1000 }
+    }
+
+    fn level(level: u32) -> Self {
+        let mut game = Self::new(Config::default());
+        game.twang.start_level(level, game.time);
+        game
+    }
+
+    fn status(&self) -> Status {
+        self.twang.status()
+    }
+
+    fn cycle(&mut self, lr: i32, fire: bool) {
+        self.twang.cycle(lr, fire, self.time);
+        self.time += FRAME_TIME;
+    }
+
+    /// Cycles the game with a fixed input for `duration` ms.
+    fn run(&mut self, lr: i32, fire: bool, duration: u32) {
+        let end = self.time + duration;
+        while self.time < end {
+            self.cycle(lr, fire);
+        }
+    }
+
+    /// Cycles the game with a fixed input until it reaches `status`, fails if
+    /// that takes longer than `timeout` ms.
+    fn run_until(&mut self, lr: i32, fire: bool, timeout: u32, status: Status) {
+        let end = self.time + timeout;
+        while self.status() != status {
+            assert!(self.time < end, "expected {:?} got stuck in {:?}", status, self.status());
+            self.cycle(lr, fire);
+        }
+    }
+}
+
+#[test]
+fn screensaver_starts_on_input() {
+    let mut game = Game::new(Config::default());
+    assert_eq!(game.status(), Status::Screensaver);
+    game.run(0, false, 2000);
+    assert_eq!(game.status(), Status::Screensaver);
+    game.cycle(0, true);
+    assert_eq!(game.status(), Status::Starting);
+    game.run_until(0, false, 4000, Status::Playing{level: 0});
+}
+
+#[test]
+fn idle_game_times_out() {
+    let mut game = Game::level(0);
+    game.run(0, false, 59_000);
+    assert_eq!(game.status(), Status::Playing{level: 0});
+    game.run_until(0, false, 2000, Status::Screensaver);
+}
+
+#[test]
+fn win_moves_on_to_the_next_level() {
+    let mut game = Game::level(0);
+    // Walk up to the enemy and kill it
+    while game.twang.player_position() < 470 {
+        game.cycle(10, false);
+    }
+    game.cycle(0, true);
+    game.run(0, false, 1000);
+    assert_eq!(game.twang.enemies_alive(), 0);
+    game.run_until(10, false, 2000, Status::Win{level: 0});
+    game.run_until(0, false, 3000, Status::Playing{level: 1});
+}
+
+#[test]
+fn living_boss_blocks_the_exit() {
+    let level = LEVEL_COUNT - 1;
+    let mut game = Game::level(level);
+    game.twang.set_god_mode(true);
+    game.run_until(10, false, 5000, Status::Playing{level});
+    while game.twang.player_position() < 999 {
+        game.cycle(10, false);
+        assert_eq!(game.status(), Status::Playing{level});
+    }
+    game.run(10, false, 1000);
+    assert!(game.twang.boss_hits_left() > 0);
+    assert_eq!(game.status(), Status::Playing{level});
+}
+
+#[test]
+fn death_costs_a_life() {
+    let mut game = Game::level(1);
+    game.run_until(10, false, 2000, Status::Death{level: 1});
+    game.run_until(0, false, 6000, Status::Lives{level: 1});
+    game.run_until(0, false, 2000, Status::Playing{level: 1});
+}
+
+#[test]
+fn last_death_is_game_over() {
+    let mut game = Game::level(1);
+    game.twang.set_lives(1);
+    game.run_until(10, false, 2000, Status::Death{level: 1});
+    game.run_until(0, false, 6000, Status::GameOver);
+    game.run_until(0, false, 3000, Status::Playing{level: 0});
+}
+
+#[test]
+fn endless_levels_never_run_out() {
+    let mut game = Game::new(Config { endless: true, ..Config::default() });
+    for level in [0, 20, 45, 100] {
+        game.twang.start_level(level, game.time);
+        game.run(0, false, 500);
+        assert_eq!(game.status(), Status::Playing{level});
+    }
+}
+
+#[test]
+fn runs_on_any_strip_length() {
+    for &length in [1, 60, 240, 512, 600, LED_STRING_MAX_LENGTH].iter() {
+        let mut game = Game::new(Config { led_string_length: length, ..Config::default() });
+        assert_eq!(game.twang.get_led_len(), length);
+        game.run(0, false, 5000);
+        game.cycle(10, false);
+        game.run_until(0, false, 4000, Status::Playing{level: 0});
+        game.run_until(10, false, 2000, Status::Death{level: 0});
+    }
+}
+
+#[test]
+#[should_panic(expected = "LED string length")]
+fn rejects_strips_longer_than_the_maximum() {
+    Game::new(Config { led_string_length: LED_STRING_MAX_LENGTH + 1, ..Config::default() });
+}
+
+#[test]
+fn god_mode_survives_anything() {
+    let mut game = Game::level(1);
+    game.twang.set_god_mode(true);
+    let end = game.time + 2000;
+    while game.time < end {
+        game.cycle(10, false);
+        assert_ne!(game.status(), Status::Death{level: 1});
+    }
+}
+
+#[test]
+fn strip_length_changes_while_running() {
+    let mut game = Game::level(1);
+    game.run(0, false, 500);
+    game.twang.set_led_len(60);
+    assert_eq!(game.twang.get_led_len(), 60);
+    game.run_until(10, false, 2000, Status::Death{level: 1});
+}
+
+#[test]
+fn dump_lists_live_entities() {
+    let game = Game::level(1);
+    let mut dump = String::new();
+    game.twang.dump(&mut dump).unwrap();
+    assert!(dump.starts_with("Playing { level: 1 }"));
+    assert!(dump.contains("Player {"));
+    assert_eq!(dump.matches("Enemy {").count(), game.twang.enemies_alive() as usize);
+}
+
+#[test]
+fn hitboxes_follow_the_player() {
+    let mut game = Game::level(0);
+    let mut hitboxes = Vec::new();
+    game.twang.hitboxes(&mut |h| hitboxes.push(h));
+    let position = game.twang.player_position();
+    assert_eq!(hitboxes[0], Hitbox::new(HitboxKind::Player, position, position, false));
+    assert_eq!(hitboxes.iter().filter(|h| h.kind == HitboxKind::Enemy).count(), game.twang.enemies_alive() as usize);
+
+    game.cycle(0, true);
+    let mut attack = None;
+    game.twang.hitboxes(&mut |h| if h.kind == HitboxKind::Attack { attack = Some(h) });
+    let attack = attack.unwrap();
+    assert!(attack.start < position && position < attack.end);
+}
+
+#[test]
+fn inspects_the_live_entities() {
+    let game = Game::level(1);
+    let mut entities = Vec::new();
+    game.twang.inspect(game.time, &mut |e| entities.push(e));
+    assert!(matches!(entities[0], Entity::Player{lives: 3, attacking: false, ..}));
+    assert_eq!(entities.iter().filter(|e| matches!(e, Entity::Enemy{..})).count(), game.twang.enemies_alive() as usize);
+    assert!(entities[1].to_string().starts_with("enemy "));
+}
+
+#[test]
+fn world_maps_onto_the_strip() {
+    let game = Game::new(Config { led_string_length: 100, ..Config::default() });
+    assert_eq!(game.twang.world_to_led(0), 0);
+    assert_eq!(game.twang.world_to_led(500), 49);
+    assert_eq!(game.twang.world_to_led(LED_STRING_VLENGTH as i32 - 1), 99);
+}
+
+#[test]
+fn logs_state_changes() {
+    let mut game = Game::level(1);
+    game.run_until(10, false, 2000, Status::Death{level: 1});
+    let mut ring = RingLogger::<8>::new();
+    game.twang.drain_log(&mut ring);
+    let events: Vec<Event> = ring.iter().map(|r| r.event).collect();
+    assert_eq!(events, [Event::State{status: Status::Death{level: 1}}]);
+}
@@ -0,0 +1,201 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Control bindings of the simulator front ends.
+//!
+//! Controls are named by the front end, keys by their name (`Left`, `A`,
+//! `Space`), gamepads as `Pad<n>.Button<n>`, `Pad<n>.Axis<n>` and
+//! `Pad<n>.Left` etc. for the d-pad. The window reads gamepads as SDL
+//! joysticks, so button and axis numbers are the raw ones of the device. A
+//! bindings file assigns them to the actions of each player, one
+//! `<player>.<action> = <control>...` per line:
+//!
+//!     p1.fire = Up Down Pad0.Button0
+//!     p2.move = Pad1.Axis0
+//!
+//! The game has one player on the strip, both players steer it, so two people
+//! can take turns without handing over the keyboard.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use twang_core::input::Input;
+
+const PLAYER_COUNT: usize = 2;
+const AXIS_MAX: i32 = 32767;
+const AXIS_DEADZONE: i32 = 6000;    // Sticks rarely center exactly
+
+const DEFAULT_BINDINGS: &str = "\
+p1.left = Left Pad0.Left
+p1.right = Right Pad0.Right
+p1.fire = Up Down Pad0.Up Pad0.Down Pad0.Button0 Pad0.Button1
+p1.move = Pad0.Axis0
+p2.left = A Pad1.Left
+p2.right = D Pad1.Right
+p2.fire = W S Pad1.Up Pad1.Down Pad1.Button0 Pad1.Button1
+p2.move = Pad1.Axis0
+";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Action {
+    Left,
+    Right,
+    Fire,
+    Move,   // Analog axis
+}
+
+#[derive(Debug)]
+struct Binding {
+    player: usize,
+    action: Action,
+    control: String,
+}
+
+#[derive(Debug)]
+pub struct Controls {
+    bindings: Vec<Binding>,
+    held: HashSet<String>,
+    axes: HashMap<String, f64>,
+}
+
+impl Controls {
+    pub fn new() -> Self {
+        Self::parse(DEFAULT_BINDINGS).unwrap()
+    }
+
+    /// Reads the bindings from a file, see the module documentation.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut bindings = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |what: &str| format!("line {}: {} in `{}`", n + 1, what, line);
+            let (target, controls) = line.split_once('=').ok_or_else(|| error("missing `=`"))?;
+            let (player, action) = target.trim().split_once('.').ok_or_else(|| error("expected <player>.<action>"))?;
+            let player = match player {
+                "p1" => 0,
+                "p2" => 1,
+                _ => return Err(error("unknown player")),
+            };
+            let action = match action {
+                "left" => Action::Left,
+                "right" => Action::Right,
+                "fire" => Action::Fire,
+                "move" => Action::Move,
+                _ => return Err(error("unknown action")),
+            };
+            for control in controls.split_whitespace() {
+                bindings.push(Binding { player, action, control: control.to_string() });
+            }
+        }
+        Ok(Self { bindings, held: HashSet::new(), axes: HashMap::new() })
+    }
+
+    pub fn press(&mut self, control: &str) {
+        self.held.insert(control.to_string());
+    }
+
+    pub fn release(&mut self, control: &str) {
+        self.held.remove(control);
+    }
+
+    /// Sets an analog axis, from -1.0 to 1.0.
+    #[cfg_attr(not(feature = "window"), allow(dead_code))]    // Only the window has gamepads
+    pub fn set_axis(&mut self, control: &str, position: f64) {
+        self.axes.insert(control.to_string(), position);
+    }
+
+    fn active(&self, player: usize, action: Action) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter().filter(move |b| b.player == player && b.action == action)
+    }
+
+    fn player(&self, player: usize) -> Input {
+        let held = |action| self.active(player, action).any(|b| self.held.contains(&b.control));
+        let buttons = Input::from_buttons(held(Action::Left), held(Action::Right), held(Action::Fire), false);
+        self.active(player, Action::Move)
+            .filter_map(|b| self.axes.get(&b.control))
+            .map(|position| Input::from_axis((position * AXIS_MAX as f64) as i32, AXIS_MAX, AXIS_DEADZONE, false))
+            .fold(buttons, Input::merge)
+    }
+
+    /// The game input of all players together.
+    pub fn input(&self) -> Input {
+        (0..PLAYER_COUNT).map(|p| self.player(p)).fold(Input::default(), Input::merge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_keys() {
+        let mut controls = Controls::new();
+        assert_eq!(controls.input(), Input::default());
+        controls.press("Left");
+        controls.press("Left");
+        controls.press("Down");
+        assert_eq!(controls.input(), Input{lr: -10, fire: true});
+        controls.release("Left");
+        controls.release("Left");
+        controls.release("Down");
+        assert_eq!(controls.input(), Input::default());
+    }
+
+    #[test]
+    fn both_players_steer() {
+        let mut controls = Controls::new();
+        controls.press("Left");
+        controls.press("D");
+        assert_eq!(controls.input().lr, 0);
+        controls.release("Left");
+        controls.set_axis("Pad1.Axis0", -1.0);
+        assert_eq!(controls.input().lr, 0);
+        controls.set_axis("Pad1.Axis0", 0.05);
+        assert_eq!(controls.input().lr, 10);
+    }
+
+    #[test]
+    fn custom_bindings() {
+        let mut controls = Controls::parse("# arcade cabinet\np1.fire = Space\np1.left = J\n").unwrap();
+        controls.press("Up");
+        controls.press("Space");
+        controls.press("J");
+        assert_eq!(controls.input(), Input{lr: -10, fire: true});
+    }
+
+    #[test]
+    fn bad_bindings() {
+        assert!(Controls::parse("p3.left = A").unwrap_err().starts_with("line 1: unknown player"));
+        assert!(Controls::parse("\np1.jump = A").unwrap_err().starts_with("line 2: unknown action"));
+        assert!(Controls::parse("p1.left A").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Writes the LED frames of a session to image files.
+//!
+//! An animated GIF plays the session back, a timeline PNG stacks one frame
+//! per row with one pixel per LED, handy for diffing.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use twang_core::Twang;
+
+use crate::led_rgb8;
+
+pub const FRAME_TIME: u32 = 16;  // Game time between recorded frames in ms
+const GIF_LED_SIZE: u16 = 4;    // GIF pixels per LED, in both directions
+const GIF_MIN_DELAY: u16 = 2;   // In 1/100 s, players slow down shorter frame delays
+
+pub struct Recorder {
+    frames: Vec<Vec<[u8; 3]>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self { frames: Vec::new() }
+    }
+
+    /// Grabs the current LED string of the game.
+    pub fn record(&mut self, twang: &mut Twang) {
+        let frame = (0..twang.get_led_len()).map(|i| led_rgb8(twang.get_led(i))).collect();
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Writes a GIF or PNG, depending on the file extension.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gif") => self.write_gif(BufWriter::new(File::create(path)?)),
+            Some("png") => self.write_png(BufWriter::new(File::create(path)?)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "export to a .gif or .png file")),
+        }
+    }
+
+    fn write_gif(&self, writer: BufWriter<File>) -> io::Result<()> {
+        let leds = self.frames.first().map_or(1, |f| f.len()) as u16;
+        let width = leds * GIF_LED_SIZE;
+        let mut encoder = gif::Encoder::new(writer, width, GIF_LED_SIZE, &[]).map_err(to_io)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(to_io)?;
+        for (i, delay) in gif_frames(self.frames.len()) {
+            let frame = &self.frames[i];
+            let mut rgb = Vec::with_capacity(width as usize * GIF_LED_SIZE as usize * 3);
+            for _ in 0..GIF_LED_SIZE {
+                for led in frame {
+                    for _ in 0..GIF_LED_SIZE {
+                        rgb.extend_from_slice(led);
+                    }
+                }
+            }
+            // Exact palette, the strip rarely shows more than 256 colors at once
+            let mut image = gif::Frame::from_rgb(width, GIF_LED_SIZE, &rgb);
+            image.delay = delay;
+            encoder.write_frame(&image).map_err(to_io)?;
+        }
+        Ok(())
+    }
+
+    fn write_png(&self, writer: BufWriter<File>) -> io::Result<()> {
+        let leds = self.frames.first().map_or(1, |f| f.len());
+        let mut encoder = png::Encoder::new(writer, leds as u32, self.frames.len().max(1) as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut png = encoder.write_header().map_err(to_io)?;
+        let mut rgb: Vec<u8> = self.frames.iter().flatten().flatten().copied().collect();
+        rgb.resize(leds * self.frames.len().max(1) * 3, 0);
+        png.write_image_data(&rgb).map_err(to_io)
+    }
+}
+
+/// Frames to put in the GIF and their delays. GIF delays are in 1/100 s,
+/// too coarse for our frames. The delays add up to the time played so far,
+/// frames that would be shown shorter than GIF_MIN_DELAY are left out.
+fn gif_frames(count: usize) -> Vec<(usize, u16)> {
+    let centis = |frames: usize| ((frames as u32 * FRAME_TIME + 5) / 10) as u16;
+    let mut gif = Vec::new();
+    let mut shown = 0;      // Frame on screen
+    let mut written = 0;    // Time the written frames add up to
+    for i in 1..count {
+        let delay = centis(i) - written;
+        if delay >= GIF_MIN_DELAY {
+            gif.push((shown, delay));
+            written += delay;
+            shown = i;
+        }
+    }
+    if count > 0 {
+        gif.push((shown, (centis(count) - written).max(GIF_MIN_DELAY)));
+    }
+    gif
+}
+
+fn to_io<E: std::error::Error + Send + Sync + 'static>(error: E) -> io::Error {
+    io::Error::other(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder(frames: usize, leds: usize) -> Recorder {
+        let mut recorder = Recorder::new();
+        for f in 0..frames {
+            recorder.frames.push((0..leds).map(|i| [(f * 10) as u8, i as u8, 0x80]).collect());
+        }
+        recorder
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rtwang-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn gif_plays_in_real_time() {
+        for count in [1, 2, 3, 10, 61, 600] {
+            let frames = gif_frames(count);
+            let total: u32 = frames.iter().map(|&(_, delay)| delay as u32).sum();
+            let expected = (count as u32 * FRAME_TIME).div_ceil(10).max(GIF_MIN_DELAY as u32);
+            assert!(total.abs_diff(expected) <= 1, "{} frames take {}", count, total);
+            assert!(frames.iter().all(|&(_, delay)| delay >= GIF_MIN_DELAY));
+            assert!(frames.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let recorder = recorder(5, 7);
+        let path = temp_path("timeline.png");
+        recorder.save(&path).unwrap();
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut rgb = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut rgb).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((info.width, info.height), (7, 5));
+        let expected: Vec<u8> = recorder.frames.iter().flatten().flatten().copied().collect();
+        assert_eq!(&rgb[..info.buffer_size()], &expected[..]);
+    }
+
+    #[test]
+    fn gif_round_trip() {
+        let recorder = recorder(5, 3);
+        let path = temp_path("session.gif");
+        recorder.save(&path).unwrap();
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        let mut decoded = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (3 * GIF_LED_SIZE, GIF_LED_SIZE));
+            decoded.push((frame.buffer.to_vec(), frame.delay));
+        }
+        std::fs::remove_file(&path).unwrap();
+        let expected = gif_frames(5);
+        assert_eq!(decoded.len(), expected.len());
+        for ((rgba, delay), (i, expected_delay)) in decoded.iter().zip(expected) {
+            assert_eq!(*delay, expected_delay);
+            // Every LED is a GIF_LED_SIZE square
+            for (p, pixel) in rgba.chunks(4).enumerate() {
+                let led = (p % (3 * GIF_LED_SIZE as usize)) / GIF_LED_SIZE as usize;
+                assert_eq!(pixel[..3], recorder.frames[i][led]);
+            }
+        }
+    }
+}
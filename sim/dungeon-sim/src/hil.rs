@@ -0,0 +1,202 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Hardware in the loop, the simulated game on a real LED strip.
+//!
+//! The bridge switches a controller running the dungeon firmware into its
+//! `frames` console mode and sends it every frame the simulator shows, while
+//! the game logic and the controls stay on the desktop. The receiver stands in
+//! for the controller on a pseudo terminal, to try the bridge without hardware.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write, stdout};
+
+use crossterm::{cursor, queue};
+use twang_core::frame::{self, Decoder};
+use twang_core::LED_STRING_MAX_LENGTH;
+
+use crate::tui::draw_strip;
+
+/// Connection to the frame receiver on a serial port.
+pub struct Bridge {
+    port: File,
+    frame: Vec<u8>,
+}
+
+impl Bridge {
+    /// Opens the serial port at `path` and starts the firmware receiver.
+    pub fn open(path: &str) -> io::Result<Self> {
+        let port = OpenOptions::new().read(true).write(true).open(path)?;
+        raw(&port)?;
+        let mut bridge = Self { port, frame: Vec::new() };
+        // The first return ends whatever was typed on the console before
+        bridge.port.write_all(b"\rframes\r")?;
+        Ok(bridge)
+    }
+
+    /// Sends the LED colors of a frame, in string order.
+    pub fn send(&mut self, leds: &[[u8; 3]]) -> io::Result<()> {
+        self.frame.clear();
+        frame::encode(leds, &mut |b| self.frame.push(b));
+        self.port.write_all(&self.frame)
+    }
+}
+
+impl Drop for Bridge {
+    /// Hands the strip back to the game on the controller.
+    fn drop(&mut self) {
+        let _ = self.send(&[]);
+    }
+}
+
+/// Raw 8 bit transfers at the 1MBaud of the firmware console.
+#[cfg(unix)]
+fn raw(port: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = port.as_raw_fd();
+    unsafe {
+        let mut termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::cfmakeraw(&mut termios);
+        // Other systems can't set this rate through termios, use stty there
+        #[cfg(target_os = "linux")]
+        libc::cfsetspeed(&mut termios, libc::B1000000);
+        if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Serial ports without termios keep their settings, set them up beforehand.
+#[cfg(not(unix))]
+fn raw(_port: &File) -> io::Result<()> {
+    Ok(())
+}
+
+/// Both ends of a pseudo terminal. The slave end stays open here, otherwise
+/// reading the master fails whenever no bridge is connected.
+#[cfg(unix)]
+struct Pty {
+    master: File,
+    _slave: File,
+    path: String,
+}
+
+#[cfg(unix)]
+impl Pty {
+    fn open() -> io::Result<Self> {
+        use std::ffi::CStr;
+        use std::os::unix::io::FromRawFd;
+
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let master = File::from_raw_fd(fd);
+            if libc::grantpt(fd) != 0 || libc::unlockpt(fd) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let name = libc::ptsname(fd);
+            if name.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let path = CStr::from_ptr(name).to_string_lossy().into_owned();
+            let slave = OpenOptions::new().read(true).write(true).open(&path)?;
+            raw(&slave)?;
+            Ok(Self { master, _slave: slave, path })
+        }
+    }
+}
+
+/// Shows the frames sent to a pseudo terminal in the terminal, until Ctrl-C.
+#[cfg(unix)]
+pub fn receive() -> io::Result<()> {
+    let mut pty = Pty::open()?;
+    println!("Receiving frames, run the simulator with --hil {}", pty.path);
+    let mut out = stdout();
+    let mut decoder = Decoder::<LED_STRING_MAX_LENGTH>::new();
+    let mut frames = 0;
+    let mut buf = [0; 4096];
+    loop {
+        let n = pty.master.read(&mut buf)?;
+        for &byte in &buf[..n] {
+            if !decoder.push(byte) {
+                continue;
+            }
+            if decoder.leds().is_empty() {
+                println!("\nStream ended after {} frames", frames);
+                frames = 0;
+                continue;
+            }
+            frames += 1;
+            queue!(out, cursor::MoveToColumn(0))?;
+            draw_strip(&mut out, decoder.leds())?;
+            out.flush()?;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn receive() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "the receiver needs a pseudo terminal"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use twang_core::{Config, Twang};
+
+    #[test]
+    fn streams_frames_through_a_pty() {
+        let mut pty = Pty::open().unwrap();
+        let mut twang = Twang::new(Config::default());
+        for frame in 0..10 {
+            twang.cycle(0, false, frame * 16);
+        }
+        let expected: Vec<[u8; 3]> = (0..twang.get_led_len()).map(|i| twang.get_led(i)).collect();
+
+        let mut bridge = Bridge::open(&pty.path).unwrap();
+        bridge.send(&expected).unwrap();
+        drop(bridge);
+
+        // The console command, the game frame and the end of the stream
+        let mut decoder = Decoder::<LED_STRING_MAX_LENGTH>::new();
+        let mut frames = Vec::new();
+        let mut buf = [0; 4096];
+        while frames.last() != Some(&Vec::new()) {
+            let n = pty.master.read(&mut buf).unwrap();
+            for &byte in &buf[..n] {
+                if decoder.push(byte) {
+                    frames.push(decoder.leds().to_vec());
+                }
+            }
+        }
+        assert_eq!(frames, vec![expected, vec![]]);
+    }
+}
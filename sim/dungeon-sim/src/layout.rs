@@ -0,0 +1,200 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Placement of the LEDs in the simulator window.
+//!
+//! Long strips don't fit in one row on screen, so they can wrap into rows like
+//! text, or fold back and forth like a serpentine strip on a panel. Each row
+//! is followed by a gap for the world overlay.
+
+pub const LED_PITCH: f64 = 13.0;        // LED plus margin at zoom 1
+const MARGIN: f64 = 1.0 / LED_PITCH;    // Margin as part of the pitch
+pub const ROW_GAP: f64 = 24.0;          // Room for the overlay below each row
+const ZOOM_STEP: f64 = 1.25;
+const ZOOM_MIN: f64 = 0.25;
+const ZOOM_MAX: f64 = 8.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Arrangement {
+    Row,        // One row scaled to the window width, zoom does not apply
+    Wrap,       // Rows running left to right
+    Serpentine, // Every other row runs right to left
+}
+
+impl Arrangement {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "row" => Some(Self::Row),
+            "wrap" => Some(Self::Wrap),
+            "serpentine" => Some(Self::Serpentine),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Row => Self::Wrap,
+            Self::Wrap => Self::Serpentine,
+            Self::Serpentine => Self::Row,
+        }
+    }
+}
+
+pub struct Layout {
+    pub arrangement: Arrangement,
+    pub zoom: f64,
+}
+
+impl Layout {
+    pub fn new(arrangement: Arrangement) -> Self {
+        Self { arrangement, zoom: 1.0 }
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom * ZOOM_STEP).min(ZOOM_MAX);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom / ZOOM_STEP).max(ZOOM_MIN);
+    }
+
+    /// Lays out `len` LEDs in a view `width` pixels wide.
+    pub fn place(&self, len: usize, width: f64) -> Placement {
+        let len = len.max(1);
+        let (pitch, columns) = match self.arrangement {
+            Arrangement::Row => (width / len as f64, len),
+            _ => {
+                let pitch = LED_PITCH * self.zoom;
+                (pitch, ((width / pitch) as usize).clamp(1, len))
+            }
+        };
+        Placement {
+            pitch,
+            columns,
+            rows: len.div_ceil(columns),
+            serpentine: self.arrangement == Arrangement::Serpentine,
+        }
+    }
+}
+
+/// Where the LEDs went, see `Layout::place`.
+#[derive(Debug)]
+pub struct Placement {
+    pitch: f64,
+    columns: usize,
+    rows: usize,
+    serpentine: bool,
+}
+
+impl Placement {
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    pub fn led_size(&self) -> f64 {
+        self.pitch * (1.0 - MARGIN)
+    }
+
+    /// Height of a row of LEDs and the overlay gap below it
+    pub fn row_height(&self) -> f64 {
+        self.pitch + ROW_GAP
+    }
+
+    pub fn height(&self) -> f64 {
+        self.rows as f64 * self.row_height()
+    }
+
+    /// Top left corner of LED `i`.
+    pub fn position(&self, i: usize) -> [f64; 2] {
+        let row = i / self.columns;
+        let mut column = i % self.columns;
+        if self.serpentine && row % 2 == 1 {
+            column = self.columns - 1 - column;
+        }
+        let margin = self.pitch * MARGIN;
+        [margin + column as f64 * self.pitch, margin + row as f64 * self.row_height()]
+    }
+
+    /// Rectangles `[x, y, width, height]` covering the LEDs `first` to `last`,
+    /// one for each row they span.
+    pub fn spans(&self, first: usize, last: usize) -> Vec<[f64; 4]> {
+        let (first, last) = (first.min(last), first.max(last));
+        (first / self.columns..=last / self.columns).map(|row| {
+            let a = self.position(first.max(row * self.columns));
+            let b = self.position(last.min(row * self.columns + self.columns - 1));
+            let x = a[0].min(b[0]);
+            [x, a[1], a[0].max(b[0]) - x + self.led_size(), self.led_size()]
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrangements() {
+        assert_eq!(Arrangement::parse("serpentine"), Some(Arrangement::Serpentine));
+        assert_eq!(Arrangement::parse("spiral"), None);
+        assert_eq!(Arrangement::Serpentine.next(), Arrangement::Row);
+    }
+
+    #[test]
+    fn row_fits_the_width() {
+        let placement = Layout::new(Arrangement::Row).place(600, 1200.0);
+        assert_eq!(placement.pitch(), 2.0);
+        assert_eq!(placement.height(), 2.0 + ROW_GAP);
+        assert!((placement.position(599)[0] + placement.led_size() - 1200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wraps_into_rows() {
+        let placement = Layout::new(Arrangement::Wrap).place(240, 1000.0);
+        // 76 LEDs per row
+        assert_eq!(placement.position(75), [1.0 + 75.0 * 13.0, 1.0]);
+        assert_eq!(placement.position(76), [1.0, 1.0 + 13.0 + ROW_GAP]);
+        assert_eq!(placement.height(), 4.0 * (13.0 + ROW_GAP));
+    }
+
+    #[test]
+    fn serpentine_folds_back() {
+        let mut layout = Layout::new(Arrangement::Serpentine);
+        layout.zoom_out();
+        layout.zoom_in();
+        let placement = layout.place(10, 4.0 * LED_PITCH);
+        assert_eq!(placement.position(3)[0], placement.position(4)[0]);
+        assert_eq!(placement.position(7)[0], placement.position(0)[0]);
+        assert_eq!(placement.position(8)[0], placement.position(0)[0]);
+    }
+
+    #[test]
+    fn spans_split_at_rows() {
+        let placement = Layout::new(Arrangement::Serpentine).place(10, 4.0 * LED_PITCH);
+        let spans = placement.spans(6, 2);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0], [1.0 + 2.0 * 13.0, 1.0, 2.0 * 13.0 - 1.0, 12.0]);
+        // 4 to 6 run right to left on the second row
+        assert_eq!(spans[1], [1.0 + 13.0, 1.0 + 13.0 + ROW_GAP, 3.0 * 13.0 - 1.0, 12.0]);
+    }
+}
@@ -22,138 +22,192 @@
  * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+#[cfg(feature = "window")]
 extern crate piston_window;
+#[cfg(feature = "window")]
 extern crate sdl2_window;
+#[cfg(feature = "window")]
 extern crate find_folder;
 
-use std::io::{Write, stdout};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use twang_core::{Config, Twang, LED_STRING_MAX_LENGTH};
+use twang_core::log::{Level, StdoutLogger};
+
+mod controls;
+mod export;
+mod hil;
+#[cfg(any(feature = "window", test))]
+mod layout;
+mod snapshot;
+mod timeline;
+mod tui;
+#[cfg(feature = "window")]
+mod window;
+
+use controls::Controls;
+use export::Recorder;
+use hil::Bridge;
+use timeline::Timeline;
 
-use piston_window::*;
-use sdl2_window::Sdl2Window;
+const LED_STRING_LENGTH: usize = 144;
+const EXPORT_FRAMES: usize = 600;       // Default export length, 10s
 
-mod twang;
-use twang::Twang;
+/// Profiler clock, microseconds since the first call
+fn clock() -> u32 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_micros() as u32
+}
 
-const LED_SIZE: u32 = 12;
-const LED_MARGIN: u32 = 1;
-const LED_STRING_LENGTH: usize = 144;
-const LED_STRING_STATUS: u32 = 13;
+/// Converts an LED to display colors, applying the inverse gamma so the
+/// screen looks like the real LEDs.
+fn led_color(led: [u8; 3]) -> [f32; 3] {
+    led.map(|c| (c as f32 / 255.0).powf(1.0 / 2.2))
+}
 
-fn main() {
+/// `led_color` as 8 bit RGB
+fn led_rgb8(led: [u8; 3]) -> [u8; 3] {
+    led_color(led).map(|c| (c * 255.0).round() as u8)
+}
+
+/// One line for each live entity, for the debug panels
+fn inspect_lines(twang: &Twang, time: u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    twang.inspect(time, &mut |entity| lines.push(entity.to_string()));
+    lines
+}
+
+/// Value following `name` on the command line
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next();
+    args.next()
+}
 
-    // Create a window for our simulated LEDs
-	let window_dimensions = [((LED_SIZE + LED_MARGIN) * LED_STRING_LENGTH as u32) + LED_MARGIN, (LED_SIZE + (LED_MARGIN * 2)) + LED_STRING_STATUS + LED_MARGIN];
-    let mut window: PistonWindow<Sdl2Window> =
-        WindowSettings::new("Rusty Spring aka rTWANG!", Size::from(window_dimensions))
-        .exit_on_esc(true)
-        .resizable(false)
-        //.graphics_api(OpenGL::V3_2)
-        .fullscreen(false)
-        .build()
-        .unwrap();
-    let assets = find_folder::Search::ParentsThenKids(3, 3)
-        .for_folder("assets").unwrap();
-    let ref font = assets.join("terminal-grotesque.ttf");
-    let mut glyphs = window.load_font(font).unwrap();
-
-    // Try to get as close as possible to 60fps
-    window.set_ups(60);
-
-    println!("dim {:?}", window_dimensions);
-
-    // Game objects
-    let mut twang = Twang::new();
-
-    // Game loop
-    let mut red: u8 = 100;
-    let mut frames = 0;
-    let mut passed = 0.0;
-    let mut ftime = 0.0;
-    let mut time: u32; // time in msec
-    let mut left = false;
-    let mut right = false;
-    let mut up = false;
-    let mut lr_input: i32 = 0;
-    let mut fps = 0.0;
-    let mut status = format!("Heya!");
-    while let Some(event) = window.next() {
-        if let Some(_) = event.render_args() {
-            window.draw_2d(&event, |context, graphics, device| {
-                clear([0.33; 4], graphics);
-                let len = twang.get_led_len();
-                for i in 0..len {
-                    let led = twang.get_led(i);
-                    // convert to f32 and apply inverse gamma to match LEDs
-                    let r = (led[0] as f32 / 255.0).powf(1.0/2.2);
-                    let g = (led[1] as f32 / 255.0).powf(1.0/2.2);
-                    let b = (led[2] as f32 / 255.0).powf(1.0/2.2);
-                    rectangle([r, g, b, 1.0],
-                              [1.0 + ((LED_SIZE + LED_MARGIN) * (i as u32)) as f64, LED_MARGIN as f64, LED_SIZE as f64, LED_SIZE as f64],
-	                          context.transform,
-	                          graphics);
-	           }
-               let transform = context.transform.trans(1.0, 25.0);
-               status = format!("FPS: {:.2} DIR: {}{}{}", fps, if left {"<"} else {" "}, if right {">"} else {" "}, if up {"^"} else {" "});
-               text::Text::new_color([1.0, 1.0, 1.0, 1.0], 10).draw(
-                &status.to_string(),
-                &mut glyphs,
-                &context.draw_state,
-                transform,
-                graphics).unwrap();
-
-               // Update glyphs before rendering.
-               glyphs.factory.encoder.flush(device);
-            });
-            frames += 1;
+/// Sets up the game from the command line, fails on a strip length the
+/// engine can't drive.
+fn new_game() -> Result<Twang, String> {
+    let led_string_length = match arg_value("--leds") {
+        Some(n) => match n.parse() {
+            Ok(n) if (1..=LED_STRING_MAX_LENGTH).contains(&n) => n,
+            _ => return Err(format!("--leds {} has to be 1 to {}", n, LED_STRING_MAX_LENGTH)),
+        },
+        None => LED_STRING_LENGTH,
+    };
+    let mut twang = Twang::new(Config {
+        led_string_length,
+        endless: std::env::args().any(|arg| arg == "--endless"),
+    });
+    if std::env::args().any(|arg| arg == "--verbose") {
+        twang.set_log_level(Level::Debug);
+    }
+    twang.profiler().set_clock(clock);
+    Ok(twang)
+}
+
+/// Continues from the `--load` snapshot file if there is one, returns the
+/// game time to go on from.
+fn load_game(twang: &mut Twang) -> Result<u32, String> {
+    match arg_value("--load") {
+        Some(path) => snapshot::load(twang, Path::new(&path)).map_err(|e| format!("{}: {}", path, e)),
+        None => Ok(0),
+    }
+}
+
+/// Runs the game without input or window and writes the frames to `path`.
+fn export(path: &str) {
+    let frames = arg_value("--frames").and_then(|n| n.parse().ok()).unwrap_or(EXPORT_FRAMES);
+    let mut twang = match new_game() {
+        Ok(twang) => twang,
+        Err(e) => {
+            println!("{}", e);
+            return;
         }
+    };
+    let mut recorder = Recorder::new();
+    let mut time = match load_game(&mut twang) {
+        Ok(time) => time,
+        Err(e) => {
+            println!("Loading the snapshot failed, {}", e);
+            return;
+        }
+    };
+    if let Some(level) = arg_value("--level").and_then(|n| n.parse().ok()) {
+        twang.start_level(level, time);
+    }
+    while recorder.len() < frames {
+        twang.cycle(0, false, time);
+        twang.drain_log(&mut StdoutLogger);
+        recorder.record(&mut twang);
+        time += export::FRAME_TIME;
+    }
+    match recorder.save(Path::new(path)) {
+        Ok(()) => println!("Wrote {} frames to {}", frames, path),
+        Err(e) => println!("Export to {} failed: {}", path, e),
+    }
+}
 
-        // Keyboard inputs
-        if let Some(button) = event.press_args() {
-            if button == Button::Keyboard(Key::Left) {
-                lr_input -= 10;
-                left = true;
-            }
-            if button == Button::Keyboard(Key::Right) {
-                lr_input += 10;
-                right = true;
-            }
-            if button == Button::Keyboard(Key::Up) {
-                up = true;
-            }
+fn main() {
+
+    if let Some(path) = arg_value("--export") {
+        export(&path);
+        return;
+    }
+
+    if std::env::args().any(|arg| arg == "--hil-receiver") {
+        if let Err(e) = hil::receive() {
+            println!("Receiver failed: {}", e);
         }
+        return;
+    }
 
-        if let Some(button) = event.release_args() {
-            if button == Button::Keyboard(Key::Left) {
-                lr_input += 10;
-                left = false;
-            }
-            if button == Button::Keyboard(Key::Right) {
-                lr_input -= 10;
-                right = false;
-            }
-            if button == Button::Keyboard(Key::Up) {
-                up = false;
+    let controls = match arg_value("--controls") {
+        Some(path) => match Controls::load(&path) {
+            Ok(controls) => controls,
+            Err(e) => {
+                println!("Bad controls file {}", e);
+                return;
             }
+        },
+        None => Controls::new(),
+    };
+    let mut twang = match new_game() {
+        Ok(twang) => twang,
+        Err(e) => {
+            println!("{}", e);
+            return;
         }
+    };
+    let mut timeline = Timeline::new();
+    match load_game(&mut twang) {
+        Ok(time) => timeline.set_time(time),
+        Err(e) => {
+            println!("Loading the snapshot failed, {}", e);
+            return;
+        }
+    }
 
-        // Game update & FPS counter
-        if let Some(u) = event.update_args() {
-            red = red.wrapping_add(1);
-
-            passed += u.dt;
-            ftime += u.dt;
-            time = (ftime * 1_000.0).round() as u32;
-
-            if passed > 0.01 {
-                fps = (frames as f64) / passed;
-                status = format!("FPS: {:.2} TIM: {}", fps, time);
-                print!("FPS: {:.2} TIM: {:010} DIR: {}{}{}\x1b[0K\r", fps, time, if left {"<"} else {" "}, if right {">"} else {" "}, if up {"^"} else {" "});
-                let _ = stdout().flush();
-                frames = 0;
-                passed = 0.0;
+    let bridge = match arg_value("--hil") {
+        Some(path) => match Bridge::open(&path) {
+            Ok(bridge) => Some(bridge),
+            Err(e) => {
+                println!("Opening the HIL port {} failed: {}", path, e);
+                return;
             }
+        },
+        None => None,
+    };
+
+    // The terminal front end is all we have without SDL
+    #[cfg(feature = "window")]
+    if !std::env::args().any(|arg| arg == "--tui") {
+        window::run(twang, controls, timeline, bridge);
+        return;
+    }
 
-            twang.cycle(lr_input, up, time);
-        }
+    if let Err(e) = tui::run(twang, controls, timeline, bridge) {
+        println!("Terminal error: {}", e);
     }
 }
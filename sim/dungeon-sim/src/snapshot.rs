@@ -0,0 +1,96 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Snapshot files, hex text like the firmware `snap` console command prints.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use twang_core::Twang;
+
+const LINE_BYTES: usize = 32;
+const BEGIN: &str = "snapshot begin";
+const END: &str = "snapshot end";
+
+fn encode(data: &[u8]) -> String {
+    let mut text = format!("{}\n", BEGIN);
+    for line in data.chunks(LINE_BYTES) {
+        let hex: String = line.iter().map(|b| format!("{:02x}", b)).collect();
+        text += &hex;
+        text.push('\n');
+    }
+    text + END + "\n"
+}
+
+/// Reads the hex lines between the markers, or all hex lines without them,
+/// skipping anything else so a whole console log can be loaded.
+fn decode(text: &str) -> Vec<u8> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let body = match lines.iter().rposition(|&l| l == BEGIN) {
+        Some(begin) => {
+            let rest = &lines[begin + 1..];
+            &rest[..rest.iter().position(|&l| l == END).unwrap_or(rest.len())]
+        }
+        None => &lines[..],
+    };
+    body.iter()
+        .filter(|l| !l.is_empty() && l.len() % 2 == 0 && l.bytes().all(|c| c.is_ascii_hexdigit()))
+        .flat_map(|l| (0..l.len()).step_by(2).map(move |i| u8::from_str_radix(&l[i..i + 2], 16).unwrap()))
+        .collect()
+}
+
+/// Saves the game at `time` to `twang-<time>.snap`.
+pub fn save(twang: &Twang, time: u32) -> io::Result<PathBuf> {
+    let mut data = Vec::new();
+    twang.save_snapshot(time, &mut |b| data.push(b));
+    let path = PathBuf::from(format!("twang-{}.snap", time));
+    fs::write(&path, encode(&data))?;
+    Ok(path)
+}
+
+/// Continues the game from a snapshot file, returns the game time it was
+/// taken at.
+pub fn load(twang: &mut Twang, path: &Path) -> io::Result<u32> {
+    let data = decode(&fs::read_to_string(path)?);
+    twang.load_snapshot(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(&encode(&data)), data);
+    }
+
+    #[test]
+    fn skips_console_noise() {
+        let log = "> snap\r\nsnapshot begin\r\n0102\r\nab\r\nsnapshot end\r\n  12\r\n";
+        assert_eq!(decode(log), [1, 2, 0xab]);
+        assert_eq!(decode("0102\nnot hex\n03\n"), [1, 2, 3]);
+    }
+}
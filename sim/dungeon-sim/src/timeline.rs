@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Pause, single stepping, slow motion, fast forward and rewind.
+//!
+//! The game runs on its own clock, advanced by the front end with the real
+//! time passed scaled by the speed. Before every game cycle the game state is
+//! saved, rewinding restores these snapshots one cycle at a time.
+
+use std::collections::VecDeque;
+
+use twang_core::{random16_get_seed, random16_set_seed, Twang};
+
+const FRAME_TIME: f64 = 16.0;                   // Game time of a single step in ms
+const SPEEDS: [f64; 7] = [0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 3;
+const HISTORY: usize = 60 * 30;                 // Snapshots kept, 30s at 60 cycles a second
+
+/// The game right before a cycle.
+struct Snapshot {
+    twang: Twang,
+    seed: u16,  // The random generator lives outside the game
+    time: f64,
+}
+
+pub struct Timeline {
+    time: f64,          // Game time in ms
+    speed: usize,       // Index into SPEEDS
+    paused: bool,
+    steps: i32,         // Pending single steps, negative steps go back
+    history: VecDeque<Snapshot>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            speed: NORMAL_SPEED,
+            paused: false,
+            steps: 0,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Game time in ms
+    pub fn time(&self) -> u32 {
+        self.time as u32
+    }
+
+    /// Moves the clock to a loaded game, the history belongs to another game.
+    pub fn set_time(&mut self, time: u32) {
+        self.time = time as f64;
+        self.history.clear();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Pauses and runs a single cycle on the next `advance`.
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.steps += 1;
+    }
+
+    /// Pauses and goes back a single cycle on the next `advance`.
+    pub fn step_back(&mut self) {
+        self.paused = true;
+        self.steps -= 1;
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    /// Runs the game for `dt` seconds of real time, calling `cycle` with the
+    /// game time for every game cycle. Fast forward runs several cycles to
+    /// keep the steps as small as at normal speed.
+    pub fn advance(&mut self, twang: &mut Twang, dt: f64, cycle: &mut dyn FnMut(&mut Twang, u32)) {
+        if self.paused {
+            while self.steps < 0 && self.rewind(twang) {
+                self.steps += 1;
+            }
+            for _ in 0..self.steps {
+                self.run(twang, FRAME_TIME, cycle);
+            }
+            self.steps = 0;
+            return;
+        }
+        let speed = SPEEDS[self.speed];
+        let cycles = speed.ceil() as usize;
+        for _ in 0..cycles {
+            self.run(twang, dt * 1000.0 * speed / cycles as f64, cycle);
+        }
+    }
+
+    fn run(&mut self, twang: &mut Twang, ms: f64, cycle: &mut dyn FnMut(&mut Twang, u32)) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(Snapshot { twang: twang.clone(), seed: random16_get_seed(), time: self.time });
+        self.time += ms;
+        cycle(twang, self.time());
+    }
+
+    /// Restores the game to before the last cycle, false once the history is
+    /// used up.
+    pub fn rewind(&mut self, twang: &mut Twang) -> bool {
+        match self.history.pop_back() {
+            Some(snapshot) => {
+                *twang = snapshot.twang;
+                random16_set_seed(snapshot.seed);
+                self.time = snapshot.time;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Short state for the status lines, empty while running normally.
+    pub fn status(&self) -> String {
+        if self.paused {
+            "PAUSED".to_string()
+        } else if self.speed != NORMAL_SPEED {
+            format!("x{}", SPEEDS[self.speed])
+        } else {
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use twang_core::Config;
+
+    fn play(twang: &mut Twang, time: u32) {
+        twang.cycle(10, false, time);
+    }
+
+    #[test]
+    fn speed_scales_game_time() {
+        let mut twang = Twang::new(Config::default());
+        let mut timeline = Timeline::new();
+        let mut cycles = 0;
+        timeline.faster();
+        timeline.advance(&mut twang, 0.016, &mut |_, _| cycles += 1);
+        assert_eq!((timeline.time(), cycles), (32, 2));
+        timeline.slower();
+        timeline.slower();
+        timeline.advance(&mut twang, 0.016, &mut |_, _| cycles += 1);
+        assert_eq!((timeline.time(), cycles), (40, 3));
+        assert_eq!(timeline.status(), "x0.5");
+    }
+
+    #[test]
+    fn pause_and_step() {
+        let mut twang = Twang::new(Config::default());
+        let mut timeline = Timeline::new();
+        timeline.toggle_pause();
+        timeline.advance(&mut twang, 0.016, &mut play);
+        assert_eq!(timeline.time(), 0);
+        timeline.step();
+        timeline.step();
+        timeline.advance(&mut twang, 0.016, &mut play);
+        assert_eq!(timeline.time(), 32);
+        timeline.step_back();
+        timeline.advance(&mut twang, 0.016, &mut play);
+        assert_eq!(timeline.time(), 16);
+        assert_eq!(timeline.status(), "PAUSED");
+    }
+
+    #[test]
+    fn rewind_replays_the_same_game() {
+        let mut twang = Twang::new(Config::default());
+        twang.start_level(1, 0);
+        let start = twang.player_position();
+        let mut timeline = Timeline::new();
+        for _ in 0..30 {
+            timeline.advance(&mut twang, 0.016, &mut play);
+        }
+        let position = twang.player_position();
+        for _ in 0..30 {
+            timeline.advance(&mut twang, 0.016, &mut play);
+        }
+        for _ in 0..30 {
+            assert!(timeline.rewind(&mut twang));
+        }
+        assert_eq!(twang.player_position(), position);
+        assert_eq!(timeline.time(), 480);
+        for _ in 0..30 {
+            assert!(timeline.rewind(&mut twang));
+        }
+        assert!(!timeline.rewind(&mut twang));
+        assert_eq!(timeline.time(), 0);
+        assert_eq!(twang.player_position(), start);
+    }
+}
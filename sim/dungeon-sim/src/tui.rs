@@ -0,0 +1,235 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! The terminal front end, for playing over SSH or without SDL.
+//!
+//! The LED string is drawn with half block characters in 24 bit color, two
+//! LEDs per character. Terminals only report key presses, unless they speak
+//! the kitty keyboard protocol, so a key counts as held until its auto repeat
+//! stops for a moment.
+
+use std::collections::HashMap;
+use std::io::{self, Write, stdout};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{cursor, execute, queue};
+
+use twang_core::Twang;
+use twang_core::log::RingLogger;
+use twang_core::profile::Section;
+
+use crate::controls::Controls;
+use crate::hil::Bridge;
+use crate::snapshot;
+use crate::timeline::Timeline;
+use crate::{inspect_lines, led_rgb8};
+
+const FRAME_TIME: Duration = Duration::from_millis(16);
+const KEY_HOLD: Duration = Duration::from_millis(250); // Covers the auto repeat delay of most terminals
+const LOG_LINES: usize = 5;
+const PANEL_LINES: usize = 16;
+
+/// Raw mode and the alternate screen while it lives.
+struct Terminal {
+    enhanced: bool, // Key release events are reported
+}
+
+impl Terminal {
+    fn open() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let enhanced = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        if enhanced {
+            execute!(stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+        }
+        Ok(Self { enhanced })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if self.enhanced {
+            let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Name of a key in the control bindings.
+fn key_name(code: KeyCode) -> Option<String> {
+    match code {
+        KeyCode::Left => Some("Left".to_string()),
+        KeyCode::Right => Some("Right".to_string()),
+        KeyCode::Up => Some("Up".to_string()),
+        KeyCode::Down => Some("Down".to_string()),
+        KeyCode::Enter => Some("Return".to_string()),
+        KeyCode::Backspace => Some("Backspace".to_string()),
+        KeyCode::Char(' ') => Some("Space".to_string()),
+        KeyCode::Char(c) => Some(c.to_ascii_uppercase().to_string()),
+        _ => None,
+    }
+}
+
+/// Plays the game in the terminal until Esc or Ctrl-C.
+pub fn run(mut twang: Twang, mut controls: Controls, mut timeline: Timeline, mut bridge: Option<Bridge>) -> io::Result<()> {
+    let terminal = Terminal::open()?;
+    let mut out = stdout();
+    let mut pressed: HashMap<String, Instant> = HashMap::new(); // Last press or repeat
+    let mut log = RingLogger::<LOG_LINES>::new();
+    let mut profile = String::new();
+    let mut profile_frames = 0;
+    let mut panel = std::env::args().any(|arg| arg == "--inspect");
+    let mut notice = String::new();
+    let mut last_frame = Instant::now();
+    let mut next_frame = last_frame;
+
+    queue!(out, Clear(ClearType::All))?;
+    loop {
+        // Handle the keys until the next frame is due
+        while event::poll(next_frame.saturating_duration_since(Instant::now()))? {
+            if let Event::Key(KeyEvent{code, modifiers, kind, ..}) = event::read()? {
+                match code {
+                    KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                    _ => (),
+                }
+                // Timeline keys repeat while held
+                if kind != KeyEventKind::Release {
+                    match code {
+                        KeyCode::F(2) if kind == KeyEventKind::Press => panel = !panel,
+                        KeyCode::F(5) if kind == KeyEventKind::Press => {
+                            notice = match snapshot::save(&twang, timeline.time()) {
+                                Ok(path) => format!("Saved {}", path.display()),
+                                Err(e) => format!("Saving the snapshot failed: {}", e),
+                            };
+                        }
+                        KeyCode::Char('p') if kind == KeyEventKind::Press => timeline.toggle_pause(),
+                        KeyCode::Char('.') => timeline.step(),
+                        KeyCode::Char(',') => timeline.step_back(),
+                        KeyCode::Char('[') => timeline.slower(),
+                        KeyCode::Char(']') => timeline.faster(),
+                        _ => (),
+                    }
+                }
+                if let Some(name) = key_name(code) {
+                    if kind == KeyEventKind::Release {
+                        controls.release(&name);
+                        pressed.remove(&name);
+                    } else {
+                        controls.press(&name);
+                        pressed.insert(name, Instant::now());
+                    }
+                }
+            }
+        }
+        // Don't try to catch up after a stall
+        next_frame = (next_frame + FRAME_TIME).max(Instant::now());
+
+        // Without release events let go of the keys that stopped repeating
+        if !terminal.enhanced {
+            let now = Instant::now();
+            pressed.retain(|name, last| {
+                let held = now.duration_since(*last) < KEY_HOLD;
+                if !held {
+                    controls.release(name);
+                }
+                held
+            });
+        }
+
+        let input = controls.input();
+        let now = Instant::now();
+        if pressed.contains_key("Backspace") {
+            timeline.rewind(&mut twang);
+        } else {
+            timeline.advance(&mut twang, now.duration_since(last_frame).as_secs_f64(), &mut |twang, time| {
+                twang.cycle(input.lr, input.fire, time);
+                twang.drain_log(&mut log);
+            });
+        }
+        last_frame = now;
+        let time = timeline.time();
+
+        // Refresh the frame section times once a second
+        profile_frames += 1;
+        if profile_frames == 60 {
+            profile = twang.profiler().to_string();
+            twang.profiler().reset();
+            profile_frames = 0;
+        }
+
+        twang.profiler().begin(Section::Upload);
+        let leds: Vec<[u8; 3]> = (0..twang.get_led_len()).map(|i| twang.get_led(i)).collect();
+        queue!(out, cursor::MoveTo(0, 0))?;
+        draw_strip(&mut out, &leds)?;
+        twang.profiler().end(Section::Upload);
+        if let Some(port) = &mut bridge {
+            if let Err(e) = port.send(&leds) {
+                notice = format!("Lost the HIL bridge: {}", e);
+                bridge = None;
+            }
+        }
+
+        let status = format!("TIM: {:010} {:6} DIR: {}{}{} US: {}", time, timeline.status(),
+            if input.lr < 0 {"<"} else {" "}, if input.lr > 0 {">"} else {" "}, if input.fire {"^"} else {" "}, profile);
+        queue!(out, cursor::MoveTo(0, 2), Print(status), Clear(ClearType::UntilNewLine))?;
+        queue!(out, cursor::MoveTo(0, 3), Print(&notice), Clear(ClearType::UntilNewLine))?;
+        for (i, record) in log.iter().enumerate() {
+            queue!(out, cursor::MoveTo(0, 4 + i as u16), Print(record), Clear(ClearType::UntilNewLine))?;
+        }
+        queue!(out, cursor::MoveTo(0, 4 + LOG_LINES as u16),
+            Print("Arrows or WASD: move and attack, F2: entities, F5: save, P: pause, ,/.: step, [/]: speed, Backspace: rewind, Esc: quit"),
+            Clear(ClearType::UntilNewLine))?;
+        let lines = if panel {inspect_lines(&twang, time)} else {Vec::new()};
+        for i in 0..PANEL_LINES {
+            let line = lines.get(i).map(String::as_str).unwrap_or("");
+            queue!(out, cursor::MoveTo(0, 6 + (LOG_LINES + i) as u16), Print(line), Clear(ClearType::UntilNewLine))?;
+        }
+        out.flush()?;
+    }
+}
+
+/// Draws two LEDs per character from the cursor on, squeezing the string to
+/// the terminal width.
+pub fn draw_strip(out: &mut impl Write, leds: &[[u8; 3]]) -> io::Result<()> {
+    let len = leds.len();
+    let width = terminal::size()?.0.max(1) as usize;
+    let halves = len.div_ceil(2).min(width) * 2;
+
+    for half in (0..halves).step_by(2) {
+        let left = led_rgb8(leds[half * len / halves]);
+        let right = led_rgb8(leds[(half + 1) * len / halves]);
+        queue!(out, SetForegroundColor(rgb(left)), SetBackgroundColor(rgb(right)), Print('▌'))?;
+    }
+    queue!(out, ResetColor, Clear(ClearType::UntilNewLine))
+}
+
+fn rgb(led: [u8; 3]) -> Color {
+    Color::Rgb { r: led[0], g: led[1], b: led[2] }
+}
@@ -0,0 +1,306 @@
+/*
+ * Copyright (c) 2020, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! The SDL window front end.
+
+use std::io::{Write, stdout};
+use std::path::Path;
+
+use piston_window::*;
+use sdl2_window::Sdl2Window;
+
+use twang_core::{Twang, LED_STRING_VLENGTH};
+use twang_core::inspect::{Hitbox, HitboxKind};
+use twang_core::log::StdoutLogger;
+use twang_core::profile::Section;
+
+use crate::controls::Controls;
+use crate::export::Recorder;
+use crate::hil::Bridge;
+use crate::layout::{Arrangement, Layout, Placement, LED_PITCH};
+use crate::snapshot;
+use crate::timeline::Timeline;
+use crate::{arg_value, inspect_lines, led_color};
+
+const LED_STRING_STATUS: f64 = 14.0;
+const WINDOW_MAX_WIDTH: f64 = 1920.0;
+const RULER_STEP: usize = 100;  // World coordinates between the ruler ticks
+const PANEL_LINE: f64 = 12.0;
+
+/// Overlay color of a hitbox, dimmed while it is harmless.
+fn hitbox_color(hitbox: &Hitbox) -> [f32; 4] {
+    let [r, g, b] = match hitbox.kind {
+        HitboxKind::Player => [0.0, 1.0, 0.0],
+        HitboxKind::Attack => [0.5, 0.8, 1.0],
+        HitboxKind::Enemy => [1.0, 0.0, 0.0],
+        HitboxKind::Lava | HitboxKind::LavaFlow | HitboxKind::BossLava => [1.0, 0.5, 0.0],
+        HitboxKind::Conveyor => [0.0, 0.3, 1.0],
+        HitboxKind::Portal => [0.7, 0.0, 1.0],
+        HitboxKind::Boss => [1.0, 0.0, 1.0],
+    };
+    [r, g, b, if hitbox.deadly {1.0} else {0.4}]
+}
+
+/// Overlay lane of a hitbox, the player and where it can go on top.
+fn hitbox_lane(hitbox: &Hitbox) -> f64 {
+    match hitbox.kind {
+        HitboxKind::Player | HitboxKind::Attack | HitboxKind::Portal | HitboxKind::Conveyor => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// Draws the hitboxes and the world coordinate ruler in the gaps below the rows.
+fn draw_overlay(twang: &Twang, hitboxes: &[Hitbox], placement: &Placement, context: &Context, graphics: &mut G2d,
+    glyphs: &mut Glyphs) {
+    let below = placement.pitch();
+    for hitbox in hitboxes {
+        let first = twang.world_to_led(hitbox.start.max(0));
+        let last = twang.world_to_led(hitbox.end.max(0));
+        for [x, y, w, _] in placement.spans(first, last) {
+            rectangle(hitbox_color(hitbox), [x, y + below + 1.0 + hitbox_lane(hitbox) * 4.0, w, 3.0],
+                context.transform, graphics);
+        }
+    }
+    for position in (0..LED_STRING_VLENGTH).step_by(RULER_STEP) {
+        let [x, y] = placement.position(twang.world_to_led(position as i32));
+        rectangle([1.0; 4], [x, y + below + 9.0, 1.0, 4.0], context.transform, graphics);
+        let _ = text::Text::new_color([1.0; 4], 8).draw(&position.to_string(), glyphs, &context.draw_state,
+            context.transform.trans(x + 2.0, y + below + 21.0), graphics);
+    }
+}
+
+pub fn run(mut twang: Twang, mut controls: Controls, mut timeline: Timeline, mut bridge: Option<Bridge>) {
+    let arrangement = arg_value("--layout").and_then(|name| Arrangement::parse(&name)).unwrap_or(Arrangement::Row);
+    let mut layout = Layout::new(arrangement);
+    let mut overlay = std::env::args().any(|arg| arg == "--overlay");
+    let mut panel = std::env::args().any(|arg| arg == "--inspect");
+
+    // Create a window for our simulated LEDs, long strips get squeezed or wrapped
+    let width = (twang.get_led_len() as f64 * LED_PITCH + 1.0).min(WINDOW_MAX_WIDTH);
+    let height = layout.place(twang.get_led_len(), width).height() + LED_STRING_STATUS;
+    let window_dimensions = [width as u32, height as u32];
+    let mut window: PistonWindow<Sdl2Window> =
+        WindowSettings::new("Rusty Spring aka rTWANG!", Size::from(window_dimensions))
+        .exit_on_esc(true)
+        .resizable(true)
+        //.graphics_api(OpenGL::V3_2)
+        .fullscreen(false)
+        .build()
+        .unwrap();
+    let assets = find_folder::Search::ParentsThenKids(3, 3)
+        .for_folder("assets").unwrap();
+    let ref font = assets.join("terminal-grotesque.ttf");
+    let mut glyphs = window.load_font(font).unwrap();
+    if let Err(e) = window.window.init_joysticks() {
+        println!("No joysticks: {}", e);
+    }
+
+    // Try to get as close as possible to 60fps
+    window.set_ups(60);
+
+    println!("dim {:?}", window_dimensions);
+
+    // Game objects
+    let record = arg_value("--record");
+    let mut recorder = Recorder::new();
+
+    // Game loop
+    let mut red: u8 = 100;
+    let mut frames = 0;
+    let mut passed = 0.0;
+    let mut rewinding = false;
+    let mut time = timeline.time(); // time in msec
+    let mut fps = 0.0;
+    let mut status = format!("Heya!");
+    let mut profile = String::new();
+    let mut profile_frames = 0;
+    while let Some(event) = window.next() {
+        let input = controls.input();
+
+        if let Some(args) = event.render_args() {
+            let mut hitboxes = Vec::new();
+            if overlay {
+                twang.hitboxes(&mut |hitbox| hitboxes.push(hitbox));
+            }
+            let len = twang.get_led_len();
+            let placement = layout.place(len, args.window_size[0]);
+            let status_y = placement.height() + LED_STRING_STATUS;
+
+            // Make room for the entity panel
+            let lines = if panel {inspect_lines(&twang, time)} else {Vec::new()};
+            let height = status_y + lines.len() as f64 * PANEL_LINE;
+            if height > args.window_size[1] {
+                window.set_size([args.window_size[0], height]);
+            }
+
+            window.draw_2d(&event, |context, graphics, device| {
+                clear([0.33; 4], graphics);
+                twang.profiler().begin(Section::Upload);
+                let size = placement.led_size();
+                for i in 0..len {
+                    let [r, g, b] = led_color(twang.get_led(i));
+                    let [x, y] = placement.position(i);
+                    rectangle([r, g, b, 1.0], [x, y, size, size], context.transform, graphics);
+                }
+                twang.profiler().end(Section::Upload);
+                if overlay {
+                    draw_overlay(&twang, &hitboxes, &placement, &context, graphics, &mut glyphs);
+                }
+                let transform = context.transform.trans(1.0, status_y - 3.0);
+                status = format!("FPS: {:.2} {} DIR: {}{}{} US: {}", fps, timeline.status(), if input.lr < 0 {"<"} else {" "}, if input.lr > 0 {">"} else {" "}, if input.fire {"^"} else {" "}, profile);
+                text::Text::new_color([1.0, 1.0, 1.0, 1.0], 10).draw(
+                    &status.to_string(),
+                    &mut glyphs,
+                    &context.draw_state,
+                    transform,
+                    graphics).unwrap();
+                for (i, line) in lines.iter().enumerate() {
+                    let transform = context.transform.trans(1.0, status_y - 3.0 + (i + 1) as f64 * PANEL_LINE);
+                    let _ = text::Text::new_color([0.8, 0.8, 0.8, 1.0], 10).draw(line, &mut glyphs, &context.draw_state,
+                        transform, graphics);
+                }
+
+                // Update glyphs before rendering.
+                glyphs.factory.encoder.flush(device);
+            });
+            frames += 1;
+        }
+
+        // Keyboard and game controller inputs
+        if let Some(button) = event.press_args() {
+            // View keys
+            match button {
+                Button::Keyboard(Key::Tab) => layout.arrangement = layout.arrangement.next(),
+                Button::Keyboard(Key::Equals) | Button::Keyboard(Key::NumPadPlus) => layout.zoom_in(),
+                Button::Keyboard(Key::Minus) | Button::Keyboard(Key::NumPadMinus) => layout.zoom_out(),
+                Button::Keyboard(Key::F1) => overlay = !overlay,
+                Button::Keyboard(Key::F2) => panel = !panel,
+                Button::Keyboard(Key::F5) => match snapshot::save(&twang, timeline.time()) {
+                    Ok(path) => println!("\nSaved {}", path.display()),
+                    Err(e) => println!("\nSaving the snapshot failed: {}", e),
+                },
+                Button::Keyboard(Key::P) => timeline.toggle_pause(),
+                Button::Keyboard(Key::Period) => timeline.step(),
+                Button::Keyboard(Key::Comma) => timeline.step_back(),
+                Button::Keyboard(Key::LeftBracket) => timeline.slower(),
+                Button::Keyboard(Key::RightBracket) => timeline.faster(),
+                Button::Keyboard(Key::Backspace) => rewinding = true,
+                _ => (),
+            }
+            match button {
+                Button::Keyboard(key) => controls.press(&format!("{:?}", key)),
+                Button::Controller(b) => controls.press(&format!("Pad{}.Button{}", b.id, b.button)),
+                Button::Hat(hat) => {
+                    for direction in ["Left", "Right", "Up", "Down"] {
+                        controls.release(&format!("Pad{}.{}", hat.id, direction));
+                    }
+                    let directions: &[&str] = match hat.state {
+                        HatState::Centered => &[],
+                        HatState::Up => &["Up"],
+                        HatState::Down => &["Down"],
+                        HatState::Left => &["Left"],
+                        HatState::Right => &["Right"],
+                        HatState::LeftUp => &["Left", "Up"],
+                        HatState::LeftDown => &["Left", "Down"],
+                        HatState::RightUp => &["Right", "Up"],
+                        HatState::RightDown => &["Right", "Down"],
+                    };
+                    for direction in directions {
+                        controls.press(&format!("Pad{}.{}", hat.id, direction));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(button) = event.release_args() {
+            if button == Button::Keyboard(Key::Backspace) {
+                rewinding = false;
+            }
+            match button {
+                Button::Keyboard(key) => controls.release(&format!("{:?}", key)),
+                Button::Controller(b) => controls.release(&format!("Pad{}.Button{}", b.id, b.button)),
+                _ => (),
+            }
+        }
+
+        if let Some(axis) = event.controller_axis_args() {
+            controls.set_axis(&format!("Pad{}.Axis{}", axis.id, axis.axis), axis.position);
+        }
+
+        // Game update & FPS counter
+        if let Some(u) = event.update_args() {
+            red = red.wrapping_add(1);
+
+            passed += u.dt;
+
+            // Hold backspace to rewind, one game cycle per update
+            if rewinding {
+                timeline.rewind(&mut twang);
+            } else {
+                timeline.advance(&mut twang, u.dt, &mut |twang, time| {
+                    twang.cycle(input.lr, input.fire, time);
+                    twang.drain_log(&mut StdoutLogger);
+                    if record.is_some() {
+                        recorder.record(twang);
+                    }
+                });
+            }
+            time = timeline.time();
+
+            if let Some(port) = &mut bridge {
+                let leds: Vec<[u8; 3]> = (0..twang.get_led_len()).map(|i| twang.get_led(i)).collect();
+                if let Err(e) = port.send(&leds) {
+                    println!("\nLost the HIL bridge: {}", e);
+                    bridge = None;
+                }
+            }
+
+            if passed > 0.01 {
+                fps = (frames as f64) / passed;
+                status = format!("FPS: {:.2} TIM: {}", fps, time);
+                print!("FPS: {:.2} TIM: {:010} DIR: {}{}{}\x1b[0K\r", fps, time, if input.lr < 0 {"<"} else {" "}, if input.lr > 0 {">"} else {" "}, if input.fire {"^"} else {" "});
+                let _ = stdout().flush();
+                frames = 0;
+                passed = 0.0;
+            }
+
+
+            // Refresh the frame section times once a second
+            profile_frames += 1;
+            if profile_frames == 60 {
+                profile = twang.profiler().to_string();
+                twang.profiler().reset();
+                profile_frames = 0;
+            }
+        }
+    }
+
+    if let Some(path) = record {
+        match recorder.save(Path::new(&path)) {
+            Ok(()) => println!("\nWrote {} frames to {}", recorder.len(), path),
+            Err(e) => println!("\nRecording to {} failed: {}", path, e),
+        }
+    }
+}
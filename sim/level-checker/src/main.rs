@@ -0,0 +1,330 @@
+/*
+ * Copyright (c) 2021, Piotr Esden-Tempski <piotr@esden.net>
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ * ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+ * WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR CONTRIBUTORS BE LIABLE FOR
+ * ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES
+ * (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES;
+ * LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND
+ * ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+ * SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::process::exit;
+
+use twang_core::{Config, Status, Twang, LED_STRING_MAX_LENGTH, LEVEL_COUNT};
+use twang_core::inspect::Entity;
+
+const FRAME_TIME: u32 = 16;             // Same frame time as the firmware
+const PLAYER_SPEED: i32 = 10;           // Same joystick input as the firmware
+const ATTEMPT_TIME_LIMIT: u32 = 60_000; // Time a single attempt may take
+const LEVEL_TIME_LIMIT: u32 = 600_000;  // Time a bot may spend on a level
+const BOT_LIVES: u8 = 3;
+const DECISION_FRAMES: u32 = 6;         // Frames an action is held before deciding again
+const ROLLOUT_FRAMES: u32 = 30;         // Frames the search looks ahead after the first action
+const ENEMY_PENALTY: i64 = 300;         // Score of killing an enemy, in world units
+const BOSS_PENALTY: i64 = 10_000;       // Score of hitting the boss, in world units
+const NAIVE_FIRE_FRAMES: u32 = 4;       // The naive bot toggles fire this often
+
+const ACTIONS: [(i32, bool); 5] = [
+    (PLAYER_SPEED, false),
+    (PLAYER_SPEED, true),
+    (0, false),
+    (0, true),
+    (-PLAYER_SPEED, false),
+];
+
+// The look ahead keeps moving in one direction and holds fire once, the long
+// presses cover the levels with charged attacks
+const ROLLOUT_MOVES: [i32; 3] = [PLAYER_SPEED, 0, -PLAYER_SPEED];
+const ROLLOUT_FIRE: [Option<(u32, u32)>; 8] = [
+    None,
+    Some((0, 1)),
+    Some((6, 7)),
+    Some((12, 13)),
+    Some((18, 19)),
+    Some((24, 25)),
+    Some((0, 12)),
+    Some((0, 24)),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Outcome {
+    Won,
+    Died,
+    Running,
+}
+
+struct Game {
+    twang: Twang,
+    time: u32,
+    frames: u32,    // Frames played since the game was forked
+    kills: i64,     // Score of the enemies killed since the game was forked
+}
+
+impl Game {
+    fn new(level: u32, config: Config) -> Self {
+        let mut twang = Twang::new(config);
+        let time = 1000;
+        twang.start_level(level, time);
+        Self { twang, time, frames: 0, kills: 0 }
+    }
+
+    /// Copy of the game to look ahead with.
+    fn fork(&self) -> Self {
+        Self { twang: self.twang.clone(), time: self.time, frames: 0, kills: 0 }
+    }
+
+    /// Runs the game for a number of frames with a fixed input, stops early
+    /// if the level is won or the player died.
+    fn run(&mut self, lr_input: i32, fire_input: bool, frames: u32) -> Outcome {
+        for _ in 0..frames {
+            let outcome = self.step(lr_input, fire_input);
+            if outcome != Outcome::Running {
+                return outcome;
+            }
+        }
+        Outcome::Running
+    }
+
+    fn step(&mut self, lr_input: i32, fire_input: bool) -> Outcome {
+        self.twang.set_lives(BOT_LIVES);
+        let enemies = self.twang.enemies_alive() as i64;
+        self.twang.cycle(lr_input, fire_input, self.time);
+        self.time += FRAME_TIME;
+        self.frames += 1;
+        // Spawners keep the enemy count up, so reward the kills themselves.
+        // Killing early is better than putting it off.
+        let killed = enemies - self.twang.enemies_alive() as i64;
+        if killed > 0 {
+            self.kills += killed * (ENEMY_PENALTY - self.frames as i64);
+        }
+        match self.twang.status() {
+            Status::Win{..} | Status::BossKilled => Outcome::Won,
+            Status::Playing{..} => Outcome::Running,
+            _ => Outcome::Died,
+        }
+    }
+
+    /// Skips the death animation and the lives display.
+    fn respawn(&mut self) {
+        while let Status::Death{..} | Status::Lives{..} = self.twang.status() {
+            self.twang.cycle(0, false, self.time);
+            self.time += FRAME_TIME;
+        }
+    }
+
+    /// Distance walked towards the exit, or towards the boss while it is
+    /// alive, as the exit stays shut until it is killed.
+    fn progress(&self) -> i64 {
+        let position = self.twang.player_position() as i64;
+        let mut boss = None;
+        self.twang.inspect(self.time, &mut |e| if let Entity::Boss{position, ..} = e {
+            boss = Some(position as i64);
+        });
+        match boss {
+            Some(boss) => 999 - (boss - position).abs(),
+            None => position,
+        }
+    }
+
+    fn score(&self, outcome: Outcome) -> i64 {
+        match outcome {
+            Outcome::Won => 1_000_000_000 - self.frames as i64,
+            // Dying later is better than dying early
+            Outcome::Died => -1_000_000_000 + self.frames as i64,
+            Outcome::Running => {
+                self.progress() + self.kills -
+                    self.twang.enemies_alive() as i64 * ENEMY_PENALTY -
+                    self.twang.boss_hits_left() as i64 * BOSS_PENALTY
+            },
+        }
+    }
+}
+
+struct Report {
+    beatable: bool,
+    best_time: Option<u32>,
+    bot_deaths: u32,
+    naive_deaths: u32,
+    naive_time: Option<u32>,
+}
+
+/// Looks ahead using a few simple move and attack patterns after each action
+/// and picks the action with the best outcome.
+fn search(game: &Game) -> (i32, bool) {
+    let mut best = ACTIONS[0];
+    let mut best_score = i64::MIN;
+    for &(lr, fire) in ACTIONS.iter() {
+        let mut first = game.fork();
+        let outcome = first.run(lr, fire, DECISION_FRAMES);
+        let mut score = first.score(outcome);
+        if outcome == Outcome::Running {
+            score = i64::MIN;
+            for &lr2 in ROLLOUT_MOVES.iter() {
+                for &pattern in ROLLOUT_FIRE.iter() {
+                    let mut second = Game { twang: first.twang.clone(), ..first };
+                    let mut outcome = Outcome::Running;
+                    for i in 0..ROLLOUT_FRAMES {
+                        let fire2 = match pattern {
+                            Some((press, release)) => i >= press && i < release,
+                            None => false,
+                        };
+                        outcome = second.step(lr2, fire2);
+                        if outcome != Outcome::Running {
+                            break;
+                        }
+                    }
+                    score = score.max(second.score(outcome));
+                }
+            }
+        }
+        if score > best_score {
+            best_score = score;
+            best = (lr, fire);
+        }
+    }
+    best
+}
+
+fn play_search(level: u32, config: Config) -> (Option<u32>, u32) {
+    let mut game = Game::new(level, config);
+    let level_start = game.time;
+    let mut attempt_start = game.time;
+    let mut deaths = 0;
+    while game.time - level_start < LEVEL_TIME_LIMIT {
+        let (lr, fire) = search(&game);
+        match game.run(lr, fire, DECISION_FRAMES) {
+            Outcome::Won => return (Some(game.time - attempt_start), deaths),
+            Outcome::Died => {
+                deaths += 1;
+                game.respawn();
+                attempt_start = game.time;
+            },
+            Outcome::Running => {
+                // Standing around for too long sends the game back to the screensaver
+                if game.time - attempt_start > ATTEMPT_TIME_LIMIT {
+                    game.twang.start_level(level, game.time);
+                    attempt_start = game.time;
+                }
+            },
+        }
+    }
+    (None, deaths)
+}
+
+fn play_naive(level: u32, config: Config) -> (Option<u32>, u32) {
+    let mut game = Game::new(level, config);
+    let level_start = game.time;
+    let mut attempt_start = game.time;
+    let mut deaths = 0;
+    let mut frame = 0;
+    while game.time - level_start < LEVEL_TIME_LIMIT {
+        let fire = frame % (2 * NAIVE_FIRE_FRAMES) < NAIVE_FIRE_FRAMES;
+        frame += 1;
+        match game.step(PLAYER_SPEED, fire) {
+            Outcome::Won => return (Some(game.time - attempt_start), deaths),
+            Outcome::Died => {
+                deaths += 1;
+                game.respawn();
+                attempt_start = game.time;
+            },
+            Outcome::Running => (),
+        }
+    }
+    (None, deaths)
+}
+
+fn check_level(level: u32, config: Config) -> Report {
+    let (best_time, bot_deaths) = play_search(level, config);
+    let (naive_time, naive_deaths) = play_naive(level, config);
+    Report {
+        beatable: best_time.is_some() || naive_time.is_some(),
+        best_time: match (best_time, naive_time) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        },
+        bot_deaths,
+        naive_deaths,
+        naive_time,
+    }
+}
+
+fn format_time(time: Option<u32>) -> String {
+    match time {
+        Some(t) => format!("{}.{:03}s", t / 1000, t % 1000),
+        None => "-".to_string(),
+    }
+}
+
+/// Value following `name` on the command line
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next();
+    args.next()
+}
+
+fn main() {
+    let led_string_length = match arg_value("--leds") {
+        Some(n) => match n.parse() {
+            Ok(n) if (1..=LED_STRING_MAX_LENGTH).contains(&n) => n,
+            _ => {
+                eprintln!("--leds {} has to be 1 to {}", n, LED_STRING_MAX_LENGTH);
+                exit(2);
+            },
+        },
+        None => Config::default().led_string_length,
+    };
+    let config = Config {
+        led_string_length,
+        endless: std::env::args().any(|arg| arg == "--endless"),
+    };
+
+    println!("LVL  BEATABLE  BEST     BOT DEATHS  NAIVE DEATHS  NAIVE TIME");
+    let mut all_beatable = true;
+    for level in 0..LEVEL_COUNT {
+        let report = check_level(level, config);
+        all_beatable &= report.beatable;
+        println!("{:3}  {:8}  {:8} {:10}  {:12}  {}",
+            level,
+            if report.beatable {"yes"} else {"NO"},
+            format_time(report.best_time),
+            report.bot_deaths,
+            report.naive_deaths,
+            format_time(report.naive_time));
+    }
+
+    if !all_beatable {
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_solves_the_first_level() {
+        let (time, deaths) = play_search(0, Config::default());
+        assert!(time.is_some());
+        assert_eq!(deaths, 0);
+    }
+
+    #[test]
+    fn search_solves_a_lava_level_on_a_long_strip() {
+        let config = Config { led_string_length: 600, ..Config::default() };
+        assert!(play_search(3, config).0.is_some());
+    }
+}